@@ -113,105 +113,106 @@ fn test_catalan_table() {
     }
 }
 
-use crate::number::modpow;
+use crate::modint::modular::{ModInt, Modulus};
+#[cfg(test)]
+use crate::modint::modular::ConstModulus;
 
 #[snippet("ModComb")]
 #[derive(Clone)]
-struct ModComb {
-    fact: Vec<i64>,
-    fact_inv: Vec<i64>,
+struct ModComb<M: Modulus> {
+    fact: Vec<ModInt<M>>,
+    fact_inv: Vec<ModInt<M>>,
     n: usize,
-    p: i64,
 }
 #[snippet("ModComb")]
-impl ModComb {
+impl<M: Modulus> ModComb<M> {
     fn initialize(ft: &mut Self) {
         let n = ft.n;
 
-        ft.fact[0] = 1;
+        ft.fact[0] = 1.into();
         for i in 1..n {
-            ft.fact[i] = (ft.fact[i-1] * i as i64) % ft.p;
+            ft.fact[i] = ft.fact[i-1] * i as i64;
         }
-        ft.fact_inv[n-1] = modpow(ft.fact[n-1], ft.p-2, ft.p);
+        ft.fact_inv[n-1] = ft.fact[n-1].recip();
         for i in (0..n-1).rev() {
-            ft.fact_inv[i] = (ft.fact_inv[i+1] * (i+1) as i64) % ft.p;
+            ft.fact_inv[i] = ft.fact_inv[i+1] * (i+1) as i64;
         }
     }
     #[doc = "O(N)"]
-    fn new(max_n: usize, p: i64) -> ModComb {
+    fn new(max_n: usize) -> ModComb<M> {
         let mut ft = ModComb {
-            fact: vec![0; max_n+1],
-            fact_inv: vec![0; max_n+1],
+            fact: vec![0.into(); max_n+1],
+            fact_inv: vec![0.into(); max_n+1],
             n: max_n+1,
-            p: p,
         };
         Self::initialize(&mut ft);
         ft
     }
-    fn fact(&self, n: usize) -> i64 {
+    fn fact(&self, n: usize) -> ModInt<M> {
         self.fact[n]
     }
     #[doc = "choose k numbers from 1..n"]
-    fn nCk(&self, n: i64, k: i64) -> i64 {
-        if n < k { return 0; }
-        (self.nPk(n, k) * self.fact_inv[k as usize]) % self.p 
+    fn nCk(&self, n: i64, k: i64) -> ModInt<M> {
+        if n < k { return 0.into(); }
+        self.nPk(n, k) * self.fact_inv[k as usize]
     }
-    fn nPk(&self, n: i64, k: i64) -> i64 {
-        if n < k { return 0; }
-        self.fact[n as usize] * self.fact_inv[(n-k) as usize] % self.p
+    fn nPk(&self, n: i64, k: i64) -> ModInt<M> {
+        if n < k { return 0.into(); }
+        self.fact[n as usize] * self.fact_inv[(n-k) as usize]
     }
     #[doc = "split k into n number as x1+x2+...xn=k"]
-    fn nHk(&self, n: i64, k: i64) -> i64 {
-        if n==0 && k==0 { return 1 }
+    fn nHk(&self, n: i64, k: i64) -> ModInt<M> {
+        if n==0 && k==0 { return 1.into() }
         self.nCk(n+k-1, k)
     }
     #[doc = "put n balls into k different boxes. In case of n=3,k+2 [[1,2],[3]]==[[3],[1,2]]"]
-    fn nSk(&self, n: i64, k: i64) -> i64 {
-        if n < k { return 0; }
-        let mut res = 0;
+    fn nSk(&self, n: i64, k: i64) -> ModInt<M> {
+        if n < k { return 0.into(); }
+        let mut res: ModInt<M> = 0.into();
         for i in 0..k+1 {
-            let v = self.nCk(k, i) * modpow(i, n, self.p) % self.p;
+            let v = self.nCk(k, i) * ModInt::<M>::new(i).pow(n);
             if (k - i) % 2 == 1 { // odd
-                res = (res + self.p - v) % self.p;
+                res -= v;
             } else { // 
-                res = (res + v) % self.p;
+                res += v;
             }
         }
-        return res * self.fact_inv[k as usize] % self.p;
+        return res * self.fact_inv[k as usize];
     }
-    fn nBk(&self, n: i64, k: i64) -> i64 {
-        0
+    fn nBk(&self, n: i64, k: i64) -> ModInt<M> {
+        0.into()
     }
 }
 #[test]
 fn test_modcomb_fact() {
-    let p = 1_000_000_007;
-    let com = ModComb::new(200000, p);
-    assert_eq!(com.fact(3), 6);
-    assert_eq!(com.fact(5), 120);
+    let com: ModComb<ConstModulus<1_000_000_007>> = ModComb::new(200000);
+    assert_eq!(com.fact(3), 6.into());
+    assert_eq!(com.fact(5), 120.into());
 }
 #[test]
 fn test_modcomb_nHk() {
-    let p = 1_000_000_007;
-    let com = ModComb::new(200000, p);
-    assert_eq!(com.nHk(10, 2), 55);
-    assert_eq!(com.nHk(10, 3), 220);
-    assert_eq!(com.nHk(10, 4), 715);
-    assert_eq!(com.nHk(400, 296), 546898535);
-    assert_eq!(com.nHk(100000, 100000), 939733670);
+    let com: ModComb<ConstModulus<1_000_000_007>> = ModComb::new(200000);
+    assert_eq!(com.nHk(10, 2), 55.into());
+    assert_eq!(com.nHk(10, 3), 220.into());
+    assert_eq!(com.nHk(10, 4), 715.into());
+    assert_eq!(com.nHk(400, 296), 546898535.into());
+    assert_eq!(com.nHk(100000, 100000), 939733670.into());
 }
 #[test]
 fn test_modcomb_nSk() {
-    let p = 1_000_000_007;
-    let com = ModComb::new(200000, p);
-    assert_eq!(com.nSk(4, 3) * com.fact(3) % p, 36);
-    assert_eq!(com.nSk(10, 3) * com.fact(3) % p, 55980);
-    assert_eq!(com.nSk(100, 100) * com.fact(100) % p, 437918130);
+    let com: ModComb<ConstModulus<1_000_000_007>> = ModComb::new(200000);
+    assert_eq!(com.nSk(4, 3) * com.fact(3), 36.into());
+    assert_eq!(com.nSk(10, 3) * com.fact(3), 55980.into());
+    assert_eq!(com.nSk(100, 100) * com.fact(100), 437918130.into());
+}
+#[test]
+fn test_modcomb_998() {
+    let com: ModComb<ConstModulus<998_244_353>> = ModComb::new(100);
+    assert_eq!(com.nCk(100, 50), 198626801.into());
 }
 #[should_panic]
 #[test]
 fn test_modcomb_mem_bound() {
-    let p = 11;
-    let modcomb = ModComb::new(8, p);
-    assert_eq!(modcomb.nPk(9, 3), 9);
-}
\ No newline at end of file
+    let modcomb: ModComb<ConstModulus<11>> = ModComb::new(8);
+    assert_eq!(modcomb.nPk(9, 3), 9.into());
+}
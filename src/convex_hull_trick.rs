@@ -1,12 +1,14 @@
 use cargo_snippet::snippet;
 
 #[snippet("ConvexHullTrick")]
-#[derive(Clone,Copy)]
-struct Line {
-    a: i64,
-    b: i64
+#[snippet("LiChaoTree")]
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct Line {
+    pub a: i64,
+    pub b: i64
 }
 #[snippet("ConvexHullTrick")]
+#[snippet("LiChaoTree")]
 impl Line {
     #[doc = "y=ax+b"]
     pub fn new(a: i64, b: i64) -> Line {
@@ -73,6 +75,308 @@ impl ConvexHullTrick {
     }
 }
 
+/// Li Chao木
+///
+/// 直線を任意の順番で追加でき、
+/// 直線を区間[l,r)だけに制限して追加することもできる。
+/// 各ノードは、その区間の中央で最良の直線を一本だけ持つ。
+///
+/// xの範囲[lo,hi)をi64で取り、触ったノードだけを作る。
+/// 中点はi128で計算するので、new_min(i64::MIN, i64::MAX)のように全域を取ってもよい。
+/// ただし範囲内の全てのxでax+bがi64に収まること。
+/// 直線の符号は反転しないので、傾きや切片がi64::MINでもよい。
+///
+/// 計算量:
+/// add_line: O(log(hi-lo))
+/// add_segment: O(log^2(hi-lo))
+/// get: O(log(hi-lo))
+
+#[snippet("LiChaoTree")]
+pub struct LiChaoTree {
+    lo: i64,
+    hi: i64,
+    /// 最大値ならtrue
+    is_max: bool,
+    line: Vec<Option<Line>>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+}
+#[snippet("LiChaoTree")]
+impl LiChaoTree {
+    fn new(lo: i64, hi: i64, is_max: bool) -> LiChaoTree {
+        assert!(lo < hi);
+        LiChaoTree {
+            lo: lo,
+            hi: hi,
+            is_max: is_max,
+            line: vec![None],
+            left: vec![0],
+            right: vec![0],
+        }
+    }
+    pub fn new_min(lo: i64, hi: i64) -> LiChaoTree {
+        Self::new(lo, hi, false)
+    }
+    pub fn new_max(lo: i64, hi: i64) -> LiChaoTree {
+        Self::new(lo, hi, true)
+    }
+    /// yがzより良い(最小なら小さい、最大なら大きい)
+    fn better(&self, y: i64, z: i64) -> bool {
+        if self.is_max { y > z } else { y < z }
+    }
+    /// [l,r)を二分する位置。r-lがi64に収まらなくてもよい
+    fn midpoint(l: i64, r: i64) -> i64 {
+        ((l as i128 + r as i128) >> 1) as i64
+    }
+    fn new_node(&mut self) -> usize {
+        self.line.push(None);
+        self.left.push(0);
+        self.right.push(0);
+        self.line.len() - 1
+    }
+    fn child(&mut self, k: usize, is_left: bool) -> usize {
+        let c = if is_left { self.left[k] } else { self.right[k] };
+        if c != 0 {
+            return c;
+        }
+        let c = self.new_node();
+        if is_left {
+            self.left[k] = c;
+        } else {
+            self.right[k] = c;
+        }
+        c
+    }
+    fn insert(&mut self, k: usize, l: i64, r: i64, line: Line) {
+        let mut k = k;
+        let (mut l, mut r) = (l, r);
+        let mut line = line;
+        loop {
+            let cur = match self.line[k] {
+                Some(cur) => cur,
+                None => {
+                    self.line[k] = Some(line);
+                    return;
+                },
+            };
+            let mid = Self::midpoint(l, r);
+            if self.better(line.y(mid), cur.y(mid)) {
+                self.line[k] = Some(line);
+                line = cur;
+            }
+            if l + 1 == r {
+                return;
+            }
+            // 中央ではself.line[k]の方が良いので、lineが勝ちうるのは片側だけ
+            let cur = self.line[k].unwrap();
+            if self.better(line.y(l), cur.y(l)) {
+                k = self.child(k, true);
+                r = mid;
+            } else if self.better(line.y(r - 1), cur.y(r - 1)) {
+                k = self.child(k, false);
+                l = mid;
+            } else {
+                return;
+            }
+        }
+    }
+    /// 直線を全域に追加する
+    pub fn add_line(&mut self, line: Line) {
+        let (lo, hi) = (self.lo, self.hi);
+        self.insert(0, lo, hi, line);
+    }
+    fn do_add_segment(&mut self, k: usize, l: i64, r: i64, a: i64, b: i64, line: Line) {
+        if r <= a || b <= l {
+            return;
+        }
+        if a <= l && r <= b {
+            self.insert(k, l, r, line);
+            return;
+        }
+        let mid = Self::midpoint(l, r);
+        let c0 = self.child(k, true);
+        self.do_add_segment(c0, l, mid, a, b, line);
+        let c1 = self.child(k, false);
+        self.do_add_segment(c1, mid, r, a, b, line);
+    }
+    /// 直線を区間[a,b)だけに追加する
+    pub fn add_segment(&mut self, a: i64, b: i64, line: Line) {
+        let (lo, hi) = (self.lo, self.hi);
+        self.do_add_segment(0, lo, hi, a, b, line);
+    }
+    /// xで最小(最大)となる直線。xを覆う直線がなければNone
+    pub fn get(&self, x: i64) -> Option<Line> {
+        assert!(self.lo <= x && x < self.hi);
+        let mut best: Option<Line> = None;
+        let mut k = 0;
+        let (mut l, mut r) = (self.lo, self.hi);
+        loop {
+            if let Some(line) = self.line[k] {
+                if best.map_or(true, |b| self.better(line.y(x), b.y(x))) {
+                    best = Some(line);
+                }
+            }
+            if l + 1 == r {
+                break;
+            }
+            let mid = Self::midpoint(l, r);
+            k = if x < mid { r = mid; self.left[k] } else { l = mid; self.right[k] };
+            if k == 0 {
+                break;
+            }
+        }
+        best
+    }
+    /// xでの最小値(最大値)
+    pub fn get_value(&self, x: i64) -> Option<i64> {
+        self.get(x).map(|line| line.y(x))
+    }
+}
+
+/// LineContainer
+///
+/// 直線を任意の順番で追加できるCHT。
+/// 傾きをキーとしたBTreeMapに、包絡線に残っている直線だけを持つ。
+/// 各直線には、その直線が最良となるxの右端pも持たせる。
+/// pは傾き順に狭義単調増加になるので、別のBTreeSetで引ける。
+///
+/// 最小値の時は符号を反転した直線をi128で持ち、交点の計算もi128で行うので、
+/// 傾きと切片はi64全域(i64::MINを含む)でよい。
+/// 答えの値はi64に収まること。
+///
+/// 計算量:
+/// add: O(logN) amortized
+/// get: O(logN)
+
+#[snippet("LineContainer")]
+pub struct LineContainer {
+    /// 最小値ならtrue。直線の符号を反転して最大値として持つ
+    is_min: bool,
+    /// 傾き -> (切片, p)
+    lines: std::collections::BTreeMap<i128, (i128, i64)>,
+    /// (p, 傾き)
+    ps: std::collections::BTreeSet<(i64, i128)>,
+}
+#[snippet("LineContainer")]
+impl LineContainer {
+    const INF: i64 = std::i64::MAX;
+    fn new(is_min: bool) -> LineContainer {
+        LineContainer {
+            is_min: is_min,
+            lines: std::collections::BTreeMap::new(),
+            ps: std::collections::BTreeSet::new(),
+        }
+    }
+    pub fn new_min() -> LineContainer {
+        Self::new(true)
+    }
+    pub fn new_max() -> LineContainer {
+        Self::new(false)
+    }
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+    fn pred(&self, k: i128) -> Option<i128> {
+        self.lines.range(..k).next_back().map(|(&k, _)| k)
+    }
+    fn succ(&self, k: i128) -> Option<i128> {
+        use std::ops::Bound::*;
+        self.lines.range((Excluded(k), Unbounded)).next().map(|(&k, _)| k)
+    }
+    fn p(&self, k: i128) -> i64 {
+        self.lines[&k].1
+    }
+    fn set_p(&mut self, k: i128, p: i64) {
+        let e = self.lines.get_mut(&k).unwrap();
+        self.ps.remove(&(e.1, k));
+        e.1 = p;
+        self.ps.insert((p, k));
+    }
+    fn remove(&mut self, k: i128) {
+        let (_, p) = self.lines.remove(&k).unwrap();
+        self.ps.remove(&(p, k));
+    }
+    /// 傾きxの直線が傾きyの直線に負ける直前のx座標(floor)。x < y
+    fn isect(&self, x: i128, y: Option<i128>) -> i64 {
+        let y = match y {
+            Some(y) => y,
+            None => return Self::INF,
+        };
+        let num = self.lines[&y].0 - self.lines[&x].0;
+        let den = x - y;
+        let mut q = num / den;
+        if (num % den != 0) && ((num < 0) != (den < 0)) {
+            q -= 1;
+        }
+        std::cmp::max(std::cmp::min(q, Self::INF as i128), std::i64::MIN as i128) as i64
+    }
+    /// 傾きxの直線のpを次の直線との交点で更新して返す
+    fn update_p(&mut self, x: i128) -> i64 {
+        let y = self.succ(x);
+        let p = self.isect(x, y);
+        self.set_p(x, p);
+        p
+    }
+    pub fn add(&mut self, line: Line) {
+        let (k, m) = if self.is_min {
+            (-(line.a as i128), -(line.b as i128))
+        } else {
+            (line.a as i128, line.b as i128)
+        };
+        if let Some(&(m0, _)) = self.lines.get(&k) {
+            if m0 >= m {
+                return;
+            }
+            self.remove(k);
+        }
+        self.lines.insert(k, (m, Self::INF));
+        self.ps.insert((Self::INF, k));
+        // 右側で不要になった直線を消す
+        loop {
+            let p = self.update_p(k);
+            match self.succ(k) {
+                Some(z) if p >= self.p(z) => self.remove(z),
+                _ => break,
+            }
+        }
+        let mut y = match self.pred(k) {
+            None => return,
+            Some(x) => {
+                if self.update_p(x) >= self.p(k) {
+                    // 追加した直線自体が不要
+                    self.remove(k);
+                    self.update_p(x);
+                }
+                x
+            },
+        };
+        // 左側で不要になった直線を消す
+        while let Some(x) = self.pred(y) {
+            if self.p(x) < self.p(y) {
+                break;
+            }
+            self.remove(y);
+            self.update_p(x);
+            y = x;
+        }
+    }
+    fn to_line(&self, k: i128, m: i128) -> Line {
+        if self.is_min { Line::new((-k) as i64, (-m) as i64) } else { Line::new(k as i64, m as i64) }
+    }
+    /// xで最小(最大)となる直線。空ならNone
+    pub fn get(&self, x: i64) -> Option<Line> {
+        self.ps.range((x, std::i128::MIN)..).next().map(|&(_, k)| self.to_line(k, self.lines[&k].0))
+    }
+    pub fn get_value(&self, x: i64) -> Option<i64> {
+        self.get(x).map(|line| (line.a as i128 * x as i128 + line.b as i128) as i64)
+    }
+    /// 包絡線を左から順に(直線, その直線が最良となるxの右端)で返す。
+    /// 最後の直線の右端はi64::MAX
+    pub fn breakpoints(&self) -> Vec<(Line, i64)> {
+        self.lines.iter().map(|(&k, &(m, p))| (self.to_line(k, m), p)).collect()
+    }
+}
+
 #[test]
 fn test_convex_hull_trick() {
     let mut cht = ConvexHullTrick::new();
@@ -88,4 +392,172 @@ fn test_convex_hull_trick() {
     cht.add(Line::new(-1,0));
     assert_eq!(cht.get_lower(-2).y(-2), -4);
     assert_eq!(cht.get_lower(2).y(2), -2);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_li_chao_tree() {
+    let mut lct = LiChaoTree::new_min(-10, 10);
+    assert_eq!(lct.get(0), None);
+    lct.add_line(Line::new(-1, 0));
+    lct.add_line(Line::new(2, 0));
+    lct.add_line(Line::new(0, -1));
+    lct.add_line(Line::new(1, 1));
+    assert_eq!(lct.get_value(-2), Some(-4));
+    assert_eq!(lct.get_value(2), Some(-2));
+    assert_eq!(lct.get(0), Some(Line::new(0, -1)));
+
+    let mut lct = LiChaoTree::new_max(0, 100);
+    lct.add_segment(10, 20, Line::new(0, 5));
+    lct.add_segment(15, 30, Line::new(1, -10));
+    assert_eq!(lct.get_value(9), None);
+    assert_eq!(lct.get_value(10), Some(5));
+    assert_eq!(lct.get_value(16), Some(6));
+    assert_eq!(lct.get_value(25), Some(15));
+    assert_eq!(lct.get_value(30), None);
+}
+
+#[test]
+fn test_li_chao_tree_extreme() {
+    let (min, max) = (std::i64::MIN, std::i64::MAX);
+    // xの範囲がi64全域
+    let mut lct = LiChaoTree::new_min(min, max);
+    lct.add_line(Line::new(0, 5));
+    lct.add_segment(-10, 10, Line::new(0, min));
+    lct.add_segment(max - 3, max, Line::new(0, -7));
+    assert_eq!(lct.get_value(min), Some(5));
+    assert_eq!(lct.get_value(-10), Some(min));
+    assert_eq!(lct.get_value(10), Some(5));
+    assert_eq!(lct.get_value(max - 1), Some(-7));
+    let mut lct = LiChaoTree::new_max(min, max);
+    lct.add_segment(min, 0, Line::new(0, max));
+    lct.add_line(Line::new(0, min));
+    assert_eq!(lct.get_value(min), Some(max));
+    assert_eq!(lct.get_value(0), Some(min));
+    // 最大値でi64::MINの傾きと切片
+    let mut lct = LiChaoTree::new_max(0, 2);
+    lct.add_line(Line::new(min, 0));
+    lct.add_line(Line::new(0, -1));
+    lct.add_line(Line::new(0, min));
+    assert_eq!(lct.get(0), Some(Line::new(min, 0)));
+    assert_eq!(lct.get(1), Some(Line::new(0, -1)));
+}
+
+#[test]
+fn test_li_chao_tree_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    let (lo, hi) = (-50, 73);
+    for &is_max in &[false, true] {
+        let mut lct = if is_max { LiChaoTree::new_max(lo, hi) } else { LiChaoTree::new_min(lo, hi) };
+        let mut segs = vec![];
+        for _ in 0..1000 {
+            if rand.rand(2) == 0 {
+                let line = Line::new(rand.rand(201) as i64 - 100, rand.rand(2001) as i64 - 1000);
+                let (a, b) = if rand.rand(3) == 0 {
+                    (lo, hi)
+                } else {
+                    let a = lo + rand.rand((hi - lo) as u64) as i64;
+                    (a, a + 1 + rand.rand((hi - a) as u64) as i64)
+                };
+                if (a, b) == (lo, hi) {
+                    lct.add_line(line);
+                } else {
+                    lct.add_segment(a, b, line);
+                }
+                segs.push((a, b, line));
+            } else {
+                let x = lo + rand.rand((hi - lo) as u64) as i64;
+                let ys = segs.iter().filter(|s| s.0 <= x && x < s.1).map(|s| s.2.y(x));
+                let expected = if is_max { ys.max() } else { ys.min() };
+                assert_eq!(lct.get_value(x), expected);
+            }
+        }
+    }
+}
+
+
+#[test]
+fn test_line_container() {
+    let mut lc = LineContainer::new_min();
+    assert_eq!(lc.get(0), None);
+    lc.add(Line::new(2, 0));
+    lc.add(Line::new(-1, 0));
+    lc.add(Line::new(1, 1));
+    lc.add(Line::new(0, -1));
+    assert_eq!(lc.get_value(-2), Some(-4));
+    assert_eq!(lc.get_value(2), Some(-2));
+    assert_eq!(lc.get(0), Some(Line::new(0, -1)));
+    // y=x+1は包絡線に乗らない
+    assert_eq!(lc.len(), 3);
+    assert_eq!(lc.breakpoints(), vec![
+        (Line::new(2, 0), -1),
+        (Line::new(0, -1), 1),
+        (Line::new(-1, 0), std::i64::MAX),
+    ]);
+
+    // 傾きも切片もi64の端
+    let mut lc = LineContainer::new_max();
+    let big = std::i64::MAX;
+    lc.add(Line::new(big, -big));
+    lc.add(Line::new(-big, -big));
+    lc.add(Line::new(0, big));
+    assert_eq!(lc.get_value(0), Some(big));
+    assert_eq!(lc.get_value(1), Some(big));
+    assert_eq!(lc.get_value(2), Some(big));
+    assert_eq!(lc.get(3), Some(Line::new(big, -big)));
+    assert_eq!(lc.get(-3), Some(Line::new(-big, -big)));
+
+    // 最小値でi64::MINの傾きと切片 (符号反転がオーバーフローしない)
+    let mut lc = LineContainer::new_min();
+    let min = std::i64::MIN;
+    lc.add(Line::new(min, 0));
+    lc.add(Line::new(0, min));
+    lc.add(Line::new(big, 0));
+    // 傾きが同じで切片が大きいので入らない
+    lc.add(Line::new(min, 5));
+    assert_eq!(lc.len(), 3);
+    assert_eq!(lc.get_value(0), Some(min));
+    assert_eq!(lc.get_value(1), Some(min));
+    assert_eq!(lc.get(-1), Some(Line::new(0, min)));
+    assert_eq!(lc.breakpoints(), vec![
+        (Line::new(big, 0), -2),
+        (Line::new(0, min), 1),
+        (Line::new(min, 0), std::i64::MAX),
+    ]);
+    let mut lc = LineContainer::new_max();
+    lc.add(Line::new(min, min));
+    lc.add(Line::new(0, 0));
+    assert_eq!(lc.get_value(-1), Some(0));
+    assert_eq!(lc.get(-2), Some(Line::new(min, min)));
+    assert_eq!(lc.get(0), Some(Line::new(0, 0)));
+    assert_eq!(lc.breakpoints(), vec![(Line::new(min, min), -1), (Line::new(0, 0), std::i64::MAX)]);
+}
+
+#[test]
+fn test_line_container_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for &(ab, xb) in &[(10, 10), (1000, 1000), (1_000_000_000_000_000_000, 2)] {
+        for &is_min in &[false, true] {
+            let mut lc = if is_min { LineContainer::new_min() } else { LineContainer::new_max() };
+            let mut lines = vec![];
+            for _ in 0..500 {
+                if rand.rand(2) == 0 {
+                    let a = rand.rand(2 * ab + 1) as i64 - ab as i64;
+                    let b = rand.rand(2 * ab + 1) as i64 - ab as i64;
+                    lc.add(Line::new(a, b));
+                    lines.push(Line::new(a, b));
+                } else {
+                    let x = rand.rand(2 * xb + 1) as i64 - xb as i64;
+                    let ys = lines.iter().map(|l| l.a as i128 * x as i128 + l.b as i128);
+                    let expected = if is_min { ys.min() } else { ys.max() };
+                    assert_eq!(lc.get_value(x).map(|y| y as i128), expected);
+                }
+                let bp = lc.breakpoints();
+                for i in 1..bp.len() {
+                    assert!(bp[i-1].1 < bp[i].1);
+                }
+            }
+        }
+    }
+}
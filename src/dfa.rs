@@ -7,7 +7,8 @@ use std::hash::Hash;
 use std::cmp::Ordering;
 
 #[snippet("Dfa")]
-trait Dfa {
+#[snippet("AhoCorasick")]
+pub trait Dfa {
     type Alphabet;
     type State;
     fn init(&self) -> Self::State;
@@ -16,7 +17,7 @@ trait Dfa {
 }
 
 #[snippet("Dfa")]
-fn count<X: Dfa>(dfa: &X, n: usize, alphabet: &[X::Alphabet], modulo: u32) -> u32
+pub fn count<X: Dfa>(dfa: &X, n: usize, alphabet: &[X::Alphabet], modulo: u32) -> u32
 where
     X::Alphabet: Copy,
     X::State: Eq + Hash + Copy,
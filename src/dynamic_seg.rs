@@ -0,0 +1,309 @@
+use cargo_snippet::snippet;
+use crate::seg::Monoid;
+use crate::seg_lazy::SEGLazyImpl;
+
+/// 動的セグ木
+///
+/// 添字の範囲[lo,hi)をi64で取り、触ったノードだけを作る。
+/// 座標圧縮なしでオンラインに更新・クエリできる。
+/// 中点はi128で計算するので、new(i64::MIN, i64::MAX)のように全域を取ってもよい。
+///
+/// ノード0は根。子が0なら未作成(中身はすべてid)を表す。
+///
+/// 計算量:
+/// update k x: O(log(hi-lo))
+/// query l r: O(log(hi-lo))
+/// メモリ: 更新1回につきO(log(hi-lo))
+
+#[snippet("DynamicSEG")]
+#[snippet("DynamicSEGLazy")]
+/// [l,r)を二分する位置。r-lがi64に収まらなくてもよい
+fn midpoint(l: i64, r: i64) -> i64 {
+    ((l as i128 + r as i128) >> 1) as i64
+}
+
+#[snippet("DynamicSEG")]
+pub struct DynamicSEG<M: Monoid> {
+    lo: i64,
+    hi: i64,
+    val: Vec<M::T>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+}
+#[snippet("DynamicSEG")]
+impl<M: Monoid> DynamicSEG<M> {
+    pub fn new(lo: i64, hi: i64) -> DynamicSEG<M> {
+        assert!(lo < hi);
+        DynamicSEG {
+            lo: lo,
+            hi: hi,
+            val: vec![M::id()],
+            left: vec![0],
+            right: vec![0],
+        }
+    }
+    fn new_node(&mut self) -> usize {
+        self.val.push(M::id());
+        self.left.push(0);
+        self.right.push(0);
+        self.val.len() - 1
+    }
+    /// a[k] = x
+    pub fn update(&mut self, k: i64, x: M::T) {
+        assert!(self.lo <= k && k < self.hi);
+        let mut path = vec![];
+        let mut node = 0;
+        let (mut l, mut r) = (self.lo, self.hi);
+        while l + 1 < r {
+            path.push(node);
+            let mid = midpoint(l, r);
+            if k < mid {
+                if self.left[node] == 0 {
+                    let c = self.new_node();
+                    self.left[node] = c;
+                }
+                node = self.left[node];
+                r = mid;
+            } else {
+                if self.right[node] == 0 {
+                    let c = self.new_node();
+                    self.right[node] = c;
+                }
+                node = self.right[node];
+                l = mid;
+            }
+        }
+        self.val[node] = x;
+        while let Some(k) = path.pop() {
+            let a = self.value(self.left[k]);
+            let b = self.value(self.right[k]);
+            self.val[k] = M::op(&a, &b);
+        }
+    }
+    fn value(&self, node: usize) -> M::T {
+        if node == 0 { M::id() } else { self.val[node].clone() }
+    }
+    pub fn get(&self, k: i64) -> M::T {
+        self.query(k, k+1)
+    }
+    fn do_query(&self, node: usize, l: i64, r: i64, a: i64, b: i64) -> M::T {
+        if r <= a || b <= l {
+            return M::id();
+        }
+        if a <= l && r <= b {
+            return self.val[node].clone();
+        }
+        let mid = midpoint(l, r);
+        let vl = if self.left[node] == 0 { M::id() } else { self.do_query(self.left[node], l, mid, a, b) };
+        let vr = if self.right[node] == 0 { M::id() } else { self.do_query(self.right[node], mid, r, a, b) };
+        M::op(&vl, &vr)
+    }
+    /// [a,b)
+    pub fn query(&self, a: i64, b: i64) -> M::T {
+        self.do_query(0, self.lo, self.hi, a, b)
+    }
+}
+
+/// 動的遅延セグ木
+///
+/// 未作成のノードの値は、作る時にinit(l, r)で決める。
+/// initは選んだ範囲のどの[l,r)でもオーバーフローしないこと。
+/// 例えば区間の長さを持つ和なら |l, r| (0, r - l) だが、
+/// これはhi-loがi64に収まる範囲でしか使えない。
+/// 全域 new(i64::MIN, i64::MAX, ..) で使えるのは、
+/// 最小値の |_, _| 0 のように区間の長さによらないinit。
+
+#[snippet("DynamicSEGLazy")]
+pub struct DynamicSEGLazy<T: SEGLazyImpl> {
+    lo: i64,
+    hi: i64,
+    init: fn(i64, i64) -> T::Monoid,
+    data: Vec<T::Monoid>,
+    lazy: Vec<T::F>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+}
+#[snippet("DynamicSEGLazy")]
+impl<T: SEGLazyImpl> DynamicSEGLazy<T> {
+    pub fn new(lo: i64, hi: i64, init: fn(i64, i64) -> T::Monoid) -> Self {
+        assert!(lo < hi);
+        DynamicSEGLazy {
+            lo: lo,
+            hi: hi,
+            init: init,
+            data: vec![init(lo, hi)],
+            lazy: vec![T::id()],
+            left: vec![0],
+            right: vec![0],
+        }
+    }
+    fn new_node(&mut self, l: i64, r: i64) -> usize {
+        self.data.push((self.init)(l, r));
+        self.lazy.push(T::id());
+        self.left.push(0);
+        self.right.push(0);
+        self.data.len() - 1
+    }
+    fn propagate(&mut self, k: usize, l: i64, r: i64) {
+        if l + 1 < r {
+            let mid = midpoint(l, r);
+            if self.left[k] == 0 {
+                let c = self.new_node(l, mid);
+                self.left[k] = c;
+            }
+            if self.right[k] == 0 {
+                let c = self.new_node(mid, r);
+                self.right[k] = c;
+            }
+        }
+        if self.lazy[k] != T::id() {
+            if l + 1 < r {
+                let (a, b) = (self.left[k], self.right[k]);
+                self.lazy[a] = T::compose(self.lazy[k], self.lazy[a]);
+                self.lazy[b] = T::compose(self.lazy[k], self.lazy[b]);
+            }
+            self.data[k] = T::ap(self.lazy[k], self.data[k]);
+            self.lazy[k] = T::id();
+        }
+    }
+    fn do_update(&mut self, a: i64, b: i64, x: T::F, k: usize, l: i64, r: i64) -> T::Monoid {
+        self.propagate(k, l, r);
+        if r <= a || b <= l {
+            self.data[k]
+        } else if a <= l && r <= b {
+            self.lazy[k] = T::compose(x, self.lazy[k]);
+            self.propagate(k, l, r);
+            self.data[k]
+        } else {
+            let mid = midpoint(l, r);
+            let (c0, c1) = (self.left[k], self.right[k]);
+            self.data[k] = T::op(
+                self.do_update(a, b, x, c0, l, mid),
+                self.do_update(a, b, x, c1, mid, r)
+            );
+            self.data[k]
+        }
+    }
+    #[doc = "[l,r)"]
+    pub fn update(&mut self, l: i64, r: i64, x: T::F) -> T::Monoid {
+        let (lo, hi) = (self.lo, self.hi);
+        self.do_update(l, r, x, 0, lo, hi)
+    }
+    fn do_query(&mut self, a: i64, b: i64, k: usize, l: i64, r: i64) -> T::Monoid {
+        if r <= a || b <= l {
+            return T::e();
+        }
+        self.propagate(k, l, r);
+        if a <= l && r <= b {
+            self.data[k]
+        } else {
+            let mid = midpoint(l, r);
+            let (c0, c1) = (self.left[k], self.right[k]);
+            T::op(
+                self.do_query(a, b, c0, l, mid),
+                self.do_query(a, b, c1, mid, r)
+            )
+        }
+    }
+    #[doc = "[l,r)"]
+    pub fn query(&mut self, l: i64, r: i64) -> T::Monoid {
+        let (lo, hi) = (self.lo, self.hi);
+        self.do_query(l, r, 0, lo, hi)
+    }
+}
+
+#[cfg(test)]
+use crate::seg::SUM;
+#[cfg(test)]
+use crate::seg_lazy::SUM_RAQ;
+
+#[test]
+fn test_dynamic_seg_huge_range() {
+    let mut seg: DynamicSEG<SUM> = DynamicSEG::new(-1_000_000_000_000_000_000, 1_000_000_000_000_000_000);
+    seg.update(-999_999_999_999_999_999, 3);
+    seg.update(0, 5);
+    seg.update(123_456_789_012_345, 7);
+    assert_eq!(seg.query(-1_000_000_000_000_000_000, 1_000_000_000_000_000_000), 15);
+    assert_eq!(seg.query(0, 123_456_789_012_345), 5);
+    assert_eq!(seg.query(1, 123_456_789_012_346), 7);
+    assert_eq!(seg.get(0), 5);
+    seg.update(0, 1);
+    assert_eq!(seg.query(-1, 1), 1);
+    assert_eq!(seg.get(1), 0);
+}
+
+#[test]
+fn test_dynamic_seg_full_range() {
+    let mut seg: DynamicSEG<SUM> = DynamicSEG::new(std::i64::MIN, std::i64::MAX);
+    seg.update(std::i64::MIN, 1);
+    seg.update(-1, 2);
+    seg.update(0, 4);
+    seg.update(std::i64::MAX - 1, 8);
+    assert_eq!(seg.query(std::i64::MIN, std::i64::MAX), 15);
+    assert_eq!(seg.query(std::i64::MIN + 1, 0), 2);
+    assert_eq!(seg.query(0, std::i64::MAX - 1), 4);
+    assert_eq!(seg.get(std::i64::MAX - 1), 8);
+    assert_eq!(seg.get(std::i64::MIN), 1);
+
+    use crate::seg_lazy::MIN_RAQ;
+    let mut seg: DynamicSEGLazy<MIN_RAQ> = DynamicSEGLazy::new(std::i64::MIN, std::i64::MAX, |_, _| 0);
+    seg.update(-3, 3, 1);
+    assert_eq!(seg.query(-3, 3), 1);
+    assert_eq!(seg.query(std::i64::MIN, std::i64::MAX), 0);
+    seg.update(std::i64::MAX - 2, std::i64::MAX, 5);
+    assert_eq!(seg.query(std::i64::MAX - 2, std::i64::MAX), 5);
+    seg.update(std::i64::MIN, std::i64::MAX, -1);
+    assert_eq!(seg.query(std::i64::MIN, std::i64::MAX), -1);
+    assert_eq!(seg.query(std::i64::MAX - 1, std::i64::MAX), 4);
+}
+
+#[test]
+fn test_dynamic_seg_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    let (lo, hi) = (-20, 37);
+    let mut seg: DynamicSEG<SUM> = DynamicSEG::new(lo, hi);
+    let mut v = vec![0; (hi - lo) as usize];
+    for _ in 0..1000 {
+        let a = lo + rand.rand((hi - lo) as u64) as i64;
+        let b = a + rand.rand((hi - a) as u64 + 1) as i64;
+        if rand.rand(2) == 0 {
+            let x = rand.rand(100) as i64;
+            v[(a - lo) as usize] = x;
+            seg.update(a, x);
+        } else {
+            assert_eq!(seg.query(a, b), v[(a - lo) as usize..(b - lo) as usize].iter().sum::<i64>());
+        }
+    }
+}
+
+#[test]
+fn test_dynamic_seg_lazy() {
+    let big = 1_000_000_000_000_000_000;
+    let mut seg: DynamicSEGLazy<SUM_RAQ> = DynamicSEGLazy::new(-big, big, |l, r| (0, r - l));
+    seg.update(-big, big, 1);
+    assert_eq!(seg.query(-10, 10), (20, 20));
+    seg.update(-5, 100_000_000_000_000_000, 2);
+    assert_eq!(seg.query(-10, 10), (50, 20));
+    assert_eq!(seg.query(99_999_999_999_999_999, 100_000_000_000_000_001), (4, 2));
+
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    let (lo, hi) = (-33, 50);
+    let mut seg: DynamicSEGLazy<SUM_RAQ> = DynamicSEGLazy::new(lo, hi, |l, r| (0, r - l));
+    let mut v = vec![0; (hi - lo) as usize];
+    for _ in 0..1000 {
+        let a = lo + rand.rand((hi - lo) as u64) as i64;
+        let b = a + rand.rand((hi - a) as u64 + 1) as i64;
+        let (ia, ib) = ((a - lo) as usize, (b - lo) as usize);
+        if rand.rand(2) == 0 {
+            let x = rand.rand(100) as i64;
+            for i in ia..ib {
+                v[i] += x;
+            }
+            seg.update(a, b, x);
+        } else {
+            assert_eq!(seg.query(a, b).0, v[ia..ib].iter().sum::<i64>());
+        }
+    }
+}
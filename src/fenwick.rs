@@ -18,12 +18,14 @@ use cargo_snippet::snippet;
 /// 計算量だけ見れば、セグ木で十分だが、係数が軽い。
 
 #[snippet("BIT")]
+#[snippet("DynamicRoLiHa")]
 #[allow(dead_code)]
 pub struct BIT<T> {
     buf: Vec<T>,
 }
 
 #[snippet("BIT")]
+#[snippet("DynamicRoLiHa")]
 #[allow(dead_code)]
 impl <T: Clone + Default + std::ops::AddAssign> BIT<T> {
     pub fn new(n: usize) -> BIT<T> {
@@ -133,4 +133,304 @@ fn test_bipartite_matching_2() {
     bpm.connect(1, 2);
     bpm.connect(2, 0);
     assert!(bpm.solve().is_none());
-}
\ No newline at end of file
+}
+/// Hopcroft-Karp法
+///
+/// 左側の頂点0..nl、右側の頂点0..nrからなる二部グラフの最大マッチング。
+/// マッチしていない左の頂点からBFSして、最短の増加路の長さで層を作り、
+/// 層に沿ったDFSで、互いに素な最短の増加路をまとめて流す。
+/// 最短の増加路の長さは反復ごとに増え、反復はO(√V)回で済む。
+///
+/// 最小点被覆 (König):
+/// マッチしていない左の頂点から交互路(左->右はどの辺でも、右->左はマッチングの辺)
+/// で到達できる集合をZとすると、(L\Z)∪(R∩Z)が最小点被覆になる。
+/// その補集合が最大独立集合。
+///
+/// 計算量 O(E√V)
+
+#[snippet("HopcroftKarp")]
+pub struct HopcroftKarp {
+    nl: usize,
+    nr: usize,
+    g: Vec<Vec<usize>>,
+    pub match_l: Vec<Option<usize>>,
+    pub match_r: Vec<Option<usize>>,
+    dist: Vec<usize>,
+    iter: Vec<usize>,
+}
+#[snippet("HopcroftKarp")]
+impl HopcroftKarp {
+    pub fn new(nl: usize, nr: usize) -> HopcroftKarp {
+        HopcroftKarp {
+            nl: nl,
+            nr: nr,
+            g: vec![vec![]; nl],
+            match_l: vec![None; nl],
+            match_r: vec![None; nr],
+            dist: vec![0; nl],
+            iter: vec![0; nl],
+        }
+    }
+    pub fn add_edge(&mut self, l: usize, r: usize) {
+        assert!(r < self.nr);
+        self.g[l].push(r);
+    }
+    fn bfs(&mut self) -> bool {
+        let inf = std::usize::MAX;
+        let mut q = std::collections::VecDeque::new();
+        for l in 0..self.nl {
+            if self.match_l[l].is_none() {
+                self.dist[l] = 0;
+                q.push_back(l);
+            } else {
+                self.dist[l] = inf;
+            }
+        }
+        let mut found = false;
+        while let Some(l) = q.pop_front() {
+            for &r in &self.g[l] {
+                match self.match_r[r] {
+                    Some(l2) => {
+                        if self.dist[l2] == inf {
+                            self.dist[l2] = self.dist[l] + 1;
+                            q.push_back(l2);
+                        }
+                    },
+                    None => found = true,
+                }
+            }
+        }
+        found
+    }
+    fn dfs(&mut self, l: usize) -> bool {
+        while self.iter[l] < self.g[l].len() {
+            let r = self.g[l][self.iter[l]];
+            self.iter[l] += 1;
+            let ok = match self.match_r[r] {
+                None => true,
+                Some(l2) => self.dist[l2] == self.dist[l] + 1 && self.dfs(l2),
+            };
+            if ok {
+                self.match_l[l] = Some(r);
+                self.match_r[r] = Some(l);
+                return true;
+            }
+        }
+        // この層からはもう増加路が無い
+        self.dist[l] = std::usize::MAX;
+        false
+    }
+    /// マッチングの大きさ
+    pub fn solve(&mut self) -> usize {
+        let mut res = self.match_l.iter().filter(|x| x.is_some()).count();
+        while self.bfs() {
+            self.iter = vec![0; self.nl];
+            for l in 0..self.nl {
+                if self.match_l[l].is_none() && self.dfs(l) {
+                    res += 1;
+                }
+            }
+        }
+        res
+    }
+    /// (左, 右)の組
+    pub fn matching(&self) -> Vec<(usize, usize)> {
+        (0..self.nl).filter_map(|l| self.match_l[l].map(|r| (l, r))).collect()
+    }
+    /// solveの後に呼ぶ。交互路で到達できるか(左, 右)
+    fn alternating_reach(&self) -> (Vec<bool>, Vec<bool>) {
+        let mut zl = vec![false; self.nl];
+        let mut zr = vec![false; self.nr];
+        let mut stack = vec![];
+        for l in 0..self.nl {
+            if self.match_l[l].is_none() {
+                zl[l] = true;
+                stack.push(l);
+            }
+        }
+        while let Some(l) = stack.pop() {
+            for &r in &self.g[l] {
+                if zr[r] {
+                    continue;
+                }
+                zr[r] = true;
+                if let Some(l2) = self.match_r[r] {
+                    if !zl[l2] {
+                        zl[l2] = true;
+                        stack.push(l2);
+                    }
+                }
+            }
+        }
+        (zl, zr)
+    }
+    /// solveの後に呼ぶ。最小点被覆の(左の頂点, 右の頂点)
+    pub fn min_vertex_cover(&self) -> (Vec<usize>, Vec<usize>) {
+        let (zl, zr) = self.alternating_reach();
+        ((0..self.nl).filter(|&l| !zl[l]).collect(), (0..self.nr).filter(|&r| zr[r]).collect())
+    }
+    /// solveの後に呼ぶ。最大独立集合の(左の頂点, 右の頂点)
+    pub fn max_independent_set(&self) -> (Vec<usize>, Vec<usize>) {
+        let (zl, zr) = self.alternating_reach();
+        ((0..self.nl).filter(|&l| zl[l]).collect(), (0..self.nr).filter(|&r| !zr[r]).collect())
+    }
+}
+
+/// ハンガリアン法
+///
+/// n×m (n<=m) のコスト行列aで、各行に異なる列を一つずつ割り当てる時の最小コスト。
+/// 行を一つずつ追加し、ポテンシャルu,vを保ちながら
+/// 被約コスト a[i][j]-u[i]-v[j] が0の辺だけで増加路を探す。
+/// 見つからなければ、ポテンシャルを最小の被約コストだけ動かす。
+///
+/// (最小コスト, 各行に割り当てた列)を返す。
+///
+/// 計算量 O(N^2 M)
+
+#[snippet("hungarian")]
+pub fn hungarian(a: &[Vec<i64>]) -> (i64, Vec<usize>) {
+    let n = a.len();
+    if n == 0 {
+        return (0, vec![]);
+    }
+    let m = a[0].len();
+    assert!(n <= m);
+    let inf = std::i64::MAX;
+    // 1-indexedで持ち、0番を番兵にする
+    let mut u = vec![0; n+1];
+    let mut v = vec![0; m+1];
+    // p[j]: 列jに割り当てた行
+    let mut p = vec![0; m+1];
+    let mut way = vec![0; m+1];
+    for i in 1..n+1 {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![inf; m+1];
+        let mut used = vec![false; m+1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = inf;
+            let mut j1 = 0;
+            for j in 1..m+1 {
+                if used[j] {
+                    continue;
+                }
+                let cur = a[i0-1][j-1] - u[i0] - v[j];
+                if cur < minv[j] {
+                    minv[j] = cur;
+                    way[j] = j0;
+                }
+                if minv[j] < delta {
+                    delta = minv[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..m+1 {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+        // 増加路に沿って割り当てを入れ替える
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+    let mut assign = vec![0; n];
+    for j in 1..m+1 {
+        if p[j] != 0 {
+            assign[p[j]-1] = j-1;
+        }
+    }
+    let cost = (0..n).map(|i| a[i][assign[i]]).sum();
+    (cost, assign)
+}
+
+#[test]
+fn test_hopcroft_karp() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..300 {
+        let nl = rand.rand(8) as usize;
+        let nr = rand.rand(8) as usize;
+        let m = if nl * nr == 0 { 0 } else { rand.rand(20) as usize };
+        let es: Vec<(usize, usize)> = (0..m).map(|_| (rand.rand(nl as u64) as usize, rand.rand(nr as u64) as usize)).collect();
+        let mut hk = HopcroftKarp::new(nl, nr);
+        let mut g_list = vec![HashSet::new(); nl+nr];
+        for &(l, r) in &es {
+            hk.add_edge(l, r);
+            g_list[l].insert(nl+r);
+            g_list[nl+r].insert(l);
+        }
+        let k = hk.solve();
+        assert_eq!(k, find_max_bipartite_matching(&g_list).len());
+
+        let matching = hk.matching();
+        assert_eq!(matching.len(), k);
+        for &(l, r) in &matching {
+            assert!(es.contains(&(l, r)));
+            assert_eq!(hk.match_r[r], Some(l));
+        }
+
+        let (cl, cr) = hk.min_vertex_cover();
+        assert_eq!(cl.len() + cr.len(), k);
+        for &(l, r) in &es {
+            assert!(cl.contains(&l) || cr.contains(&r));
+        }
+        let (il, ir) = hk.max_independent_set();
+        assert_eq!(il.len() + ir.len(), nl + nr - k);
+        for &(l, r) in &es {
+            assert!(!(il.contains(&l) && ir.contains(&r)));
+        }
+    }
+}
+
+#[test]
+fn test_hungarian() {
+    let a = vec![
+        vec![3, 4, 9],
+        vec![2, 8, 1],
+    ];
+    assert_eq!(hungarian(&a), (4, vec![0, 2]));
+
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..300 {
+        let n = rand.rand(6) as usize;
+        let m = n + rand.rand(3) as usize;
+        let a: Vec<Vec<i64>> = (0..n).map(|_| (0..m).map(|_| rand.rand(41) as i64 - 20).collect()).collect();
+        fn brute(a: &[Vec<i64>], i: usize, used: &mut Vec<bool>) -> i64 {
+            if i == a.len() {
+                return 0;
+            }
+            let mut best = std::i64::MAX;
+            for j in 0..used.len() {
+                if !used[j] {
+                    used[j] = true;
+                    best = std::cmp::min(best, a[i][j] + brute(a, i+1, used));
+                    used[j] = false;
+                }
+            }
+            best
+        }
+        let (cost, assign) = hungarian(&a);
+        assert_eq!(cost, brute(&a, 0, &mut vec![false; m]));
+        let mut cols = assign.clone();
+        cols.sort();
+        cols.dedup();
+        assert_eq!(cols.len(), n);
+    }
+}
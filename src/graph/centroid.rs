@@ -1,5 +1,6 @@
 use cargo_snippet::snippet;
 use std::collections::HashMap;
+use crate::graph::csr::Tree;
 
 /// 木の重心分解
 /// 
@@ -13,46 +14,28 @@ use std::collections::HashMap;
 
 #[snippet("TreeCentroid")]
 pub struct Centroid {
-    pub g: Vec<Vec<usize>>,
     pub centroid: Vec<usize>,
-    subsize: Vec<usize>,
 }
 #[snippet("TreeCentroid")]
 impl Centroid {
-    pub fn new(n: usize) -> Centroid {
-        Centroid {
-            g: vec![vec![];n],
-            centroid: vec![],
-            subsize: vec![0;n],
-        }
-    }
-    pub fn connect(&mut self, u: usize, v: usize) {
-        self.g[u].push(v);
-        self.g[v].push(u);
-    }
-    #[doc = "O(nlogn)"]
-    pub fn build(&mut self) {
-        let n = self.g.len();
-        self.rec(0, n);
-    }
-    fn rec(&mut self, u: usize, par: usize) {
-        let n = self.g.len();
-        self.subsize[u] = 1;
-        let mut is_centroid = true;
-        for i in 0..self.g[u].len() {
-            let ch = self.g[u][i];
-            if ch == par { continue; }
-            self.rec(ch,u);
-            if self.subsize[ch] > n/2 {
-                is_centroid = false;
+    #[doc = "O(n)"]
+    pub fn new<E>(t: &Tree<E>) -> Centroid {
+        let n = t.len();
+        let mut subsize = vec![1; n];
+        let mut is_centroid = vec![true; n];
+        for &u in t.order.iter().rev() {
+            if n - subsize[u] > n/2 {
+                is_centroid[u] = false;
+            }
+            if let Some(p) = t.par[u] {
+                if subsize[u] > n/2 {
+                    is_centroid[p] = false;
+                }
+                subsize[p] += subsize[u];
             }
-            self.subsize[u] += self.subsize[ch];
-        }
-        if n - self.subsize[u] > n/2 {
-            is_centroid = false;
         }
-        if is_centroid {
-            self.centroid.push(u);
+        Centroid {
+            centroid: (0..n).filter(|&u| is_centroid[u]).collect(),
         }
     }
 }
@@ -158,12 +141,8 @@ fn test_centroid() {
         (23,21),
     ];
     let n = 24;
-    let mut g = Centroid::new(n);
-    for &(u,v) in &e {
-        g.connect(u,v);
-    }
-    g.build();
-    g.centroid.sort();
+    let es: Vec<_> = e.iter().map(|&(u,v)| (u,v,())).collect();
+    let g = Centroid::new(&Tree::from_edges(n, 0, &es));
     assert_eq!(g.centroid, vec![2,12]);
 
     let mut nodeid = vec![0;24];
@@ -0,0 +1,266 @@
+use cargo_snippet::snippet;
+use crate::template::Parser;
+use std::io::BufRead;
+use std::str::FromStr;
+
+/// 隣接リストをCSR形式(一本の配列)で持つグラフ
+///
+/// 頂点vから出る辺は edges[start[v]..start[v+1]] に並ぶ。
+/// 辺には任意のデータEを載せられる(コストなど)。
+/// 構築後は辺を追加できない代わりに、Vec<Vec<_>>よりキャッシュ効率が良い。
+///
+/// 計算量:
+/// 構築 O(V+E)
+
+#[snippet("Graph")]
+#[snippet("Tree")]
+#[snippet("SCC")]
+#[snippet("TwoSat")]
+#[snippet("Lowlink")]
+#[snippet("TopologicalSort")]
+#[snippet("CycleDetection")]
+#[snippet("chu_liu_edmonds")]
+#[snippet("LCA")]
+#[snippet("HLDecomposition")]
+#[snippet("TreeCentroid")]
+#[snippet("EularTour")]
+#[snippet("tree_diameter")]
+#[snippet("dijkstra_graph")]
+#[snippet("bfs01_graph")]
+#[snippet("count_shortest_paths")]
+#[derive(Clone, Debug)]
+pub struct Graph<E> {
+    pub n: usize,
+    start: Vec<usize>,
+    /// (行き先, データ)
+    edges: Vec<(usize, E)>,
+}
+#[snippet("Graph")]
+#[snippet("Tree")]
+#[snippet("SCC")]
+#[snippet("TwoSat")]
+#[snippet("Lowlink")]
+#[snippet("TopologicalSort")]
+#[snippet("CycleDetection")]
+#[snippet("chu_liu_edmonds")]
+#[snippet("LCA")]
+#[snippet("HLDecomposition")]
+#[snippet("TreeCentroid")]
+#[snippet("EularTour")]
+#[snippet("tree_diameter")]
+#[snippet("dijkstra_graph")]
+#[snippet("bfs01_graph")]
+#[snippet("count_shortest_paths")]
+impl<E: Clone> Graph<E> {
+    /// 有向グラフ
+    pub fn from_edges(n: usize, es: &[(usize, usize, E)]) -> Graph<E> {
+        let mut start = vec![0; n+1];
+        for &(u, _, _) in es {
+            start[u+1] += 1;
+        }
+        for i in 0..n {
+            start[i+1] += start[i];
+        }
+        let mut pos = start.clone();
+        let mut edges: Vec<Option<(usize, E)>> = vec![None; es.len()];
+        for (u, v, e) in es.iter().cloned() {
+            edges[pos[u]] = Some((v, e));
+            pos[u] += 1;
+        }
+        Graph {
+            n: n,
+            start: start,
+            edges: edges.into_iter().map(|e| e.unwrap()).collect(),
+        }
+    }
+    /// 無向グラフ。各辺を両向きに張る
+    pub fn from_undirected_edges(n: usize, es: &[(usize, usize, E)]) -> Graph<E> {
+        let mut all = Vec::with_capacity(es.len() * 2);
+        for (u, v, e) in es.iter().cloned() {
+            all.push((v, u, e.clone()));
+            all.push((u, v, e));
+        }
+        Self::from_edges(n, &all)
+    }
+}
+#[snippet("Graph")]
+#[snippet("Tree")]
+#[snippet("SCC")]
+#[snippet("TwoSat")]
+#[snippet("Lowlink")]
+#[snippet("TopologicalSort")]
+#[snippet("CycleDetection")]
+#[snippet("chu_liu_edmonds")]
+#[snippet("LCA")]
+#[snippet("HLDecomposition")]
+#[snippet("TreeCentroid")]
+#[snippet("EularTour")]
+#[snippet("tree_diameter")]
+#[snippet("dijkstra_graph")]
+#[snippet("bfs01_graph")]
+#[snippet("count_shortest_paths")]
+impl<E> Graph<E> {
+    /// vから出る辺
+    pub fn adj(&self, v: usize) -> &[(usize, E)] {
+        &self.edges[self.start[v]..self.start[v+1]]
+    }
+    pub fn degree(&self, v: usize) -> usize {
+        self.start[v+1] - self.start[v]
+    }
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+}
+
+/// 根付き木
+///
+/// 無向のGraphに加えて、根からBFSした結果を持つ。
+/// par[v]: 親 (根はNone)
+/// depth[v]: 根からの辺の数
+/// order: BFSの順。親は必ず子より先に来るので、
+/// 逆順に回せば葉から根へのDPになる。
+///
+/// 計算量:
+/// 構築 O(V)
+
+#[snippet("Tree")]
+#[snippet("LCA")]
+#[snippet("HLDecomposition")]
+#[snippet("TreeCentroid")]
+#[snippet("EularTour")]
+#[snippet("tree_diameter")]
+#[derive(Clone, Debug)]
+pub struct Tree<E> {
+    pub g: Graph<E>,
+    pub root: usize,
+    pub par: Vec<Option<usize>>,
+    pub depth: Vec<usize>,
+    pub order: Vec<usize>,
+}
+#[snippet("Tree")]
+#[snippet("LCA")]
+#[snippet("HLDecomposition")]
+#[snippet("TreeCentroid")]
+#[snippet("EularTour")]
+#[snippet("tree_diameter")]
+impl<E: Clone> Tree<E> {
+    /// n-1本の無向辺から作る
+    pub fn from_edges(n: usize, root: usize, es: &[(usize, usize, E)]) -> Tree<E> {
+        assert_eq!(es.len() + 1, n);
+        Self::from_graph(Graph::from_undirected_edges(n, es), root)
+    }
+}
+#[snippet("Tree")]
+#[snippet("LCA")]
+#[snippet("HLDecomposition")]
+#[snippet("TreeCentroid")]
+#[snippet("EularTour")]
+#[snippet("tree_diameter")]
+impl<E> Tree<E> {
+    /// 無向グラフとして張られた木から作る
+    pub fn from_graph(g: Graph<E>, root: usize) -> Tree<E> {
+        let n = g.n;
+        let mut par = vec![None; n];
+        let mut depth = vec![0; n];
+        let mut order = Vec::with_capacity(n);
+        let mut visited = vec![false; n];
+        visited[root] = true;
+        order.push(root);
+        let mut i = 0;
+        while i < order.len() {
+            let u = order[i];
+            i += 1;
+            for &(v, _) in g.adj(u) {
+                if !visited[v] {
+                    visited[v] = true;
+                    par[v] = Some(u);
+                    depth[v] = depth[u] + 1;
+                    order.push(v);
+                }
+            }
+        }
+        assert_eq!(order.len(), n, "not connected");
+        Tree {
+            g: g,
+            root: root,
+            par: par,
+            depth: depth,
+            order: order,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.g.n
+    }
+    pub fn adj(&self, v: usize) -> &[(usize, E)] {
+        self.g.adj(v)
+    }
+    /// vの子への辺
+    pub fn children<'a>(&'a self, v: usize) -> impl Iterator<Item = &'a (usize, E)> + 'a {
+        let p = self.par[v];
+        self.g.adj(v).iter().filter(move |e| Some(e.0) != p)
+    }
+}
+
+/// 辺リストを読む
+///
+/// "u v" がm行続く形式。頂点番号は1-indexedで、0-indexedにして返す。
+/// Graph::from_edgesなどにそのまま渡せる。
+
+#[snippet("read_edges")]
+pub fn read_edges<R: BufRead>(p: &mut Parser<R>, m: usize) -> Vec<(usize, usize, ())> {
+    (0..m).map(|_| {
+        let u: usize = p.next().unwrap();
+        let v: usize = p.next().unwrap();
+        (u-1, v-1, ())
+    }).collect()
+}
+
+/// "u v w" がm行続く形式
+#[snippet("read_edges")]
+pub fn read_weighted_edges<R: BufRead, E: FromStr>(p: &mut Parser<R>, m: usize) -> Vec<(usize, usize, E)> {
+    (0..m).map(|_| {
+        let u: usize = p.next().unwrap();
+        let v: usize = p.next().unwrap();
+        let w: E = p.next().ok().unwrap();
+        (u-1, v-1, w)
+    }).collect()
+}
+
+#[test]
+fn test_graph_csr() {
+    let g = Graph::from_edges(4, &[(0, 1, 'a'), (2, 3, 'b'), (0, 2, 'c')]);
+    assert_eq!(g.adj(0), &[(1, 'a'), (2, 'c')]);
+    assert_eq!(g.adj(1), &[]);
+    assert_eq!(g.adj(2), &[(3, 'b')]);
+    assert_eq!(g.degree(0), 2);
+    assert_eq!(g.num_edges(), 3);
+    let g = Graph::from_undirected_edges(3, &[(0, 1, 5), (1, 2, 7)]);
+    assert_eq!(g.adj(1), &[(0, 5), (2, 7)]);
+    assert_eq!(g.num_edges(), 4);
+}
+
+#[test]
+fn test_tree_csr() {
+    let t = Tree::from_edges(5, 0, &[(0, 1, ()), (2, 0, ()), (1, 3, ()), (1, 4, ())]);
+    assert_eq!(t.par, [None, Some(0), Some(0), Some(1), Some(1)]);
+    assert_eq!(t.depth, [0, 1, 1, 2, 2]);
+    assert_eq!(t.order, [0, 1, 2, 3, 4]);
+    let ch: Vec<usize> = t.children(1).map(|e| e.0).collect();
+    assert_eq!(ch, [3, 4]);
+}
+
+#[test]
+fn test_read_edges() {
+    let mut p = Parser::from_str("3 2\n1 2\n3 2\n");
+    let n: usize = p.next().unwrap();
+    let m: usize = p.next().unwrap();
+    let g = Graph::from_edges(n, &read_edges(&mut p, m));
+    assert_eq!(g.adj(0), &[(1, ())]);
+    assert_eq!(g.adj(2), &[(1, ())]);
+
+    let mut p = Parser::from_str("3\n1 2 5\n2 3 -1\n");
+    let n: usize = p.next().unwrap();
+    let t = Tree::from_edges(n, 2, &read_weighted_edges::<_, i64>(&mut p, n-1));
+    assert_eq!(t.par, [Some(1), Some(2), None]);
+    assert_eq!(t.adj(1), &[(0, 5), (2, -1)]);
+}
@@ -1,5 +1,5 @@
 use cargo_snippet::snippet;
-use std::collections::HashSet;
+use crate::graph::csr::Graph;
 
 /// 有向グラフに対してサイクルの存在判定を行う。
 /// 
@@ -13,17 +13,17 @@ use std::collections::HashSet;
 
 // verified: GRL_4_A
 #[snippet("CycleDetection")]
-fn cycle_detection_directed(g: &[Vec<usize>]) -> bool {
-    let n = g.len();
-    let mut in_g = vec![HashSet::new();n];
+pub fn cycle_detection_directed<E>(g: &Graph<E>) -> bool {
+    let n = g.n;
+    let mut indeg = vec![0; n];
     for v in 0..n {
-        for &u in &g[v] {
-            in_g[u].insert(v);
+        for &(u, _) in g.adj(v) {
+            indeg[u] += 1;
         }
     }
     let mut v_indegree0 = vec![];
     for v in 0..n {
-        if in_g[v].len() == 0 {
+        if indeg[v] == 0 {
             v_indegree0.push(v);
         }
     }
@@ -31,8 +31,9 @@ fn cycle_detection_directed(g: &[Vec<usize>]) -> bool {
     let mut m=0;
     while let Some(v) = v_indegree0.pop() {
         m += 1;
-        for &to in &g[v] {
-            if in_g[to].remove(&v) && in_g[to].is_empty() {
+        for &(to, _) in g.adj(v) {
+            indeg[to] -= 1;
+            if indeg[to] == 0 {
                 v_indegree0.push(to);
             }
         }
@@ -40,24 +41,6 @@ fn cycle_detection_directed(g: &[Vec<usize>]) -> bool {
 
     m != n
 }
-#[snippet("CycleDetection")]
-struct CycleDetection {
-    g: Vec<Vec<usize>>,
-}
-#[snippet("CycleDetection")]
-impl CycleDetection {
-    pub fn new(n: usize) -> CycleDetection {
-        CycleDetection {
-            g: vec![vec![];n]
-        }
-    }
-    pub fn add_edge(&mut self, u: usize, v: usize) {
-        self.g[u].push(v);
-    }
-    pub fn solve(&self) -> bool {
-        cycle_detection_directed(&self.g)
-    }
-}
 
 #[test]
 fn test_detect_cycle_directed_0() {
@@ -66,14 +49,14 @@ fn test_detect_cycle_directed_0() {
         vec![2],
         vec![],
     ];
-    let mut g = CycleDetection::new(3);
+    let mut es = vec![];
     for u in 0..e.len() {
         let vs = e[u].clone();
         for v in vs {
-            g.add_edge(u, v);
+            es.push((u, v, ()));
         }
     }
-    assert_eq!(g.solve(), false);
+    assert_eq!(cycle_detection_directed(&Graph::from_edges(3, &es)), false);
 }
 
 #[test]
@@ -86,12 +69,12 @@ fn test_detect_cycle_directed_1() {
         vec![5],
         vec![3],
     ];
-    let mut g = CycleDetection::new(6);
+    let mut es = vec![];
     for u in 0..e.len() {
         let vs = e[u].clone();
         for v in vs {
-            g.add_edge(u,v);
+            es.push((u,v,()));
         }
     }
-    assert_eq!(g.solve(), true);
+    assert_eq!(cycle_detection_directed(&Graph::from_edges(6, &es)), true);
 }
\ No newline at end of file
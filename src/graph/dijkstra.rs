@@ -0,0 +1,287 @@
+use cargo_snippet::snippet;
+use crate::graph::csr::Graph;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// ダイクストラ法で使うコスト
+///
+/// 全順序で、加算で小さくならないこと(非負)。
+/// f64はtotal::Totalで包む。
+/// タプルは辞書順で比べ、成分ごとに足す。
+
+#[snippet("Cost")]
+#[snippet("dijkstra_graph")]
+#[snippet("count_shortest_paths")]
+pub trait Cost: Copy + Ord {
+    fn zero() -> Self;
+    fn add(self, other: Self) -> Self;
+}
+#[snippet("Cost")]
+#[snippet("dijkstra_graph")]
+#[snippet("count_shortest_paths")]
+macro_rules! impl_cost_int {
+    ($($t:ty),*) => {
+        $(
+            impl Cost for $t {
+                fn zero() -> Self { 0 }
+                fn add(self, other: Self) -> Self { self + other }
+            }
+        )*
+    };
+}
+#[snippet("Cost")]
+#[snippet("dijkstra_graph")]
+#[snippet("count_shortest_paths")]
+impl_cost_int!(u32, u64, usize, i32, i64);
+#[snippet("Cost")]
+#[snippet("dijkstra_graph")]
+#[snippet("count_shortest_paths")]
+impl Cost for crate::total::Total<f64> {
+    fn zero() -> Self { crate::total::Total(0.0) }
+    fn add(self, other: Self) -> Self { crate::total::Total(self.0 + other.0) }
+}
+#[snippet("Cost")]
+#[snippet("dijkstra_graph")]
+#[snippet("count_shortest_paths")]
+impl<A: Cost, B: Cost> Cost for (A, B) {
+    fn zero() -> Self { (A::zero(), B::zero()) }
+    fn add(self, other: Self) -> Self { (self.0.add(other.0), self.1.add(other.1)) }
+}
+
+/// 最短路の結果
+///
+/// dist[v]: 始点からの距離。届かなければNone
+/// parent[v]: 最短路木での親。始点と届かない頂点はNone
+
+#[snippet("dijkstra_graph")]
+#[snippet("bfs01_graph")]
+#[derive(Clone, Debug)]
+pub struct ShortestPath<C> {
+    pub dist: Vec<Option<C>>,
+    pub parent: Vec<Option<usize>>,
+}
+#[snippet("dijkstra_graph")]
+#[snippet("bfs01_graph")]
+impl<C> ShortestPath<C> {
+    /// 始点からvまでの頂点列。届かなければNone
+    pub fn path_to(&self, v: usize) -> Option<Vec<usize>> {
+        self.dist[v].as_ref()?;
+        let mut path = vec![v];
+        let mut v = v;
+        while let Some(p) = self.parent[v] {
+            path.push(p);
+            v = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// ダイクストラ法 (多始点)
+///
+/// 始点を全て距離0としてキューに入れる。
+///
+/// 計算量 O(E logV)
+
+#[snippet("dijkstra_graph")]
+pub fn dijkstra_multi<C: Cost>(g: &Graph<C>, sources: &[usize]) -> ShortestPath<C> {
+    let n = g.n;
+    let mut dist: Vec<Option<C>> = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut heap = BinaryHeap::new();
+    for &s in sources {
+        dist[s] = Some(C::zero());
+        heap.push(Reverse((C::zero(), s)));
+    }
+    while let Some(Reverse((d, v))) = heap.pop() {
+        if dist[v] != Some(d) {
+            continue;
+        }
+        for &(to, c) in g.adj(v) {
+            let nd = d.add(c);
+            if dist[to].map_or(true, |x| nd < x) {
+                dist[to] = Some(nd);
+                parent[to] = Some(v);
+                heap.push(Reverse((nd, to)));
+            }
+        }
+    }
+    ShortestPath {
+        dist: dist,
+        parent: parent,
+    }
+}
+
+#[snippet("dijkstra_graph")]
+pub fn dijkstra<C: Cost>(g: &Graph<C>, s: usize) -> ShortestPath<C> {
+    dijkstra_multi(g, &[s])
+}
+
+/// 0-1 BFS
+///
+/// コストが0か1のグラフでの最短路。
+/// コスト0の辺で進んだ頂点はdequeの前に、1なら後ろに入れる。
+///
+/// 計算量 O(V+E)
+
+#[snippet("bfs01_graph")]
+pub fn bfs01_multi(g: &Graph<u64>, sources: &[usize]) -> ShortestPath<u64> {
+    let n = g.n;
+    let mut dist: Vec<Option<u64>> = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut done = vec![false; n];
+    let mut deque = std::collections::VecDeque::new();
+    for &s in sources {
+        dist[s] = Some(0);
+        deque.push_back(s);
+    }
+    while let Some(v) = deque.pop_front() {
+        if done[v] {
+            continue;
+        }
+        done[v] = true;
+        let d = dist[v].unwrap();
+        for &(to, c) in g.adj(v) {
+            assert!(c <= 1);
+            let nd = d + c;
+            if dist[to].map_or(true, |x| nd < x) {
+                dist[to] = Some(nd);
+                parent[to] = Some(v);
+                if c == 0 {
+                    deque.push_front(to);
+                } else {
+                    deque.push_back(to);
+                }
+            }
+        }
+    }
+    ShortestPath {
+        dist: dist,
+        parent: parent,
+    }
+}
+
+#[snippet("bfs01_graph")]
+pub fn bfs01(g: &Graph<u64>, s: usize) -> ShortestPath<u64> {
+    bfs01_multi(g, &[s])
+}
+
+/// 最短路の本数 mod p
+///
+/// ダイクストラ法で、距離が同じになる辺から来た本数を足し合わせる。
+/// 頂点は距離の順に確定するので、確定した時点の本数は正しい。
+/// ただしコスト0の辺があると確定の順序が崩れるので、コストは正であること。
+///
+/// 計算量 O(E logV)
+
+#[snippet("count_shortest_paths")]
+pub fn count_shortest_paths<C: Cost>(g: &Graph<C>, s: usize, p: u64) -> (Vec<Option<C>>, Vec<u64>) {
+    let n = g.n;
+    let mut dist: Vec<Option<C>> = vec![None; n];
+    let mut ways = vec![0; n];
+    let mut heap = BinaryHeap::new();
+    dist[s] = Some(C::zero());
+    ways[s] = 1 % p;
+    heap.push(Reverse((C::zero(), s)));
+    while let Some(Reverse((d, v))) = heap.pop() {
+        if dist[v] != Some(d) {
+            continue;
+        }
+        for &(to, c) in g.adj(v) {
+            assert!(c > C::zero());
+            let nd = d.add(c);
+            match dist[to] {
+                Some(x) if x < nd => {},
+                Some(x) if x == nd => {
+                    ways[to] = (ways[to] + ways[v]) % p;
+                },
+                _ => {
+                    dist[to] = Some(nd);
+                    ways[to] = ways[v];
+                    heap.push(Reverse((nd, to)));
+                },
+            }
+        }
+    }
+    (dist, ways)
+}
+
+#[test]
+fn test_dijkstra_graph() {
+    let g = Graph::from_edges(5, &[(0, 1, 4u64), (0, 2, 1), (2, 1, 2), (1, 3, 1), (2, 3, 5)]);
+    let sp = dijkstra(&g, 0);
+    assert_eq!(sp.dist, [Some(0), Some(3), Some(1), Some(4), None]);
+    assert_eq!(sp.path_to(3), Some(vec![0, 2, 1, 3]));
+    assert_eq!(sp.path_to(0), Some(vec![0]));
+    assert_eq!(sp.path_to(4), None);
+
+    let sp = dijkstra_multi(&g, &[0, 1]);
+    assert_eq!(sp.dist, [Some(0), Some(0), Some(1), Some(1), None]);
+    assert_eq!(sp.path_to(3), Some(vec![1, 3]));
+}
+
+#[test]
+fn test_dijkstra_graph_f64_and_tuple() {
+    use crate::total::Total;
+    let g = Graph::from_edges(3, &[(0, 1, Total(0.5)), (1, 2, Total(0.25)), (0, 2, Total(1.0))]);
+    let sp = dijkstra(&g, 0);
+    assert_eq!(sp.dist[2], Some(Total(0.75)));
+
+    // (距離, 辺の数)の辞書順: 距離が同じなら辺の少ない方
+    let g = Graph::from_edges(3, &[(0, 1, (1u64, 1u64)), (1, 2, (1, 1)), (0, 2, (2, 1))]);
+    let sp = dijkstra(&g, 0);
+    assert_eq!(sp.dist[2], Some((2, 1)));
+    assert_eq!(sp.path_to(2), Some(vec![0, 2]));
+}
+
+#[test]
+fn test_dijkstra_graph_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..50 {
+        let n = 1 + rand.rand(30) as usize;
+        let m = rand.rand(100) as usize;
+        let es: Vec<(usize, usize, u64)> = (0..m).map(|_| (rand.rand(n as u64) as usize, rand.rand(n as u64) as usize, 1 + rand.rand(5))).collect();
+        let g = Graph::from_edges(n, &es);
+        // ベルマンフォード的に距離と本数を求める
+        let inf = std::u64::MAX;
+        let mut d = vec![inf; n];
+        d[0] = 0;
+        for _ in 0..n {
+            for &(u, v, c) in &es {
+                if d[u] != inf && d[u] + c < d[v] {
+                    d[v] = d[u] + c;
+                }
+            }
+        }
+        let sp = dijkstra(&g, 0);
+        let (dist, ways) = count_shortest_paths(&g, 0, 1_000_000_007);
+        let mut order: Vec<usize> = (0..n).filter(|&v| d[v] != inf).collect();
+        order.sort_by_key(|&v| d[v]);
+        let mut cnt = vec![0u64; n];
+        cnt[0] = 1;
+        for &v in &order {
+            for &(u, w, c) in &es {
+                if w == v && d[u] != inf && d[u] + c == d[v] {
+                    cnt[v] += cnt[u];
+                }
+            }
+        }
+        for v in 0..n {
+            let expected = if d[v] == inf { None } else { Some(d[v]) };
+            assert_eq!(sp.dist[v], expected);
+            assert_eq!(dist[v], expected);
+            if expected.is_some() {
+                assert_eq!(ways[v], cnt[v] % 1_000_000_007);
+                let path = sp.path_to(v).unwrap();
+                assert_eq!(path[0], 0);
+                let len: u64 = path.windows(2).map(|w| g.adj(w[0]).iter().filter(|e| e.0 == w[1]).map(|e| e.1).min().unwrap()).sum();
+                assert_eq!(len, d[v]);
+            }
+        }
+
+        let es01: Vec<(usize, usize, u64)> = es.iter().map(|&(u, v, c)| (u, v, c % 2)).collect();
+        let g01 = Graph::from_edges(n, &es01);
+        assert_eq!(bfs01(&g01, 0).dist, dijkstra(&g01, 0).dist);
+    }
+}
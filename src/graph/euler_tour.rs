@@ -1,55 +1,46 @@
 use cargo_snippet::snippet;
+use crate::graph::csr::Tree;
 
 #[snippet("EularTour")]
 #[derive(Debug)]
-struct EularTour {
-    g: Vec<Vec<usize>>,
-    n: usize,
-    vid: usize,
-    enter: Vec<usize>,
-    leave: Vec<usize>,
+pub struct EularTour {
+    pub enter: Vec<usize>,
+    pub leave: Vec<usize>,
 }
 #[snippet("EularTour")]
 impl EularTour {
-    pub fn new(n: usize) -> EularTour {
-        EularTour {
-            g: vec![vec![];n],
-            n: n,
-            vid: 0,
-            enter: vec![n;n],
-            leave: vec![n;n],
+    /// 根から深さ優先で辿り、入る時と出る時に番号を振る
+    pub fn new<E>(t: &Tree<E>) -> EularTour {
+        let n = t.len();
+        let mut enter = vec![n; n];
+        let mut leave = vec![n; n];
+        let mut vid = 0;
+        // (頂点, 次に見る辺の位置)
+        let mut stack = vec![(t.root, 0)];
+        enter[t.root] = vid;
+        vid += 1;
+        while let Some(&mut (u, ref mut i)) = stack.last_mut() {
+            if let Some(&(v, _)) = t.adj(u).get(*i) {
+                *i += 1;
+                if Some(v) == t.par[u] { continue; }
+                enter[v] = vid;
+                vid += 1;
+                stack.push((v, 0));
+            } else {
+                leave[u] = vid;
+                vid += 1;
+                stack.pop();
+            }
         }
-    }
-    pub fn connect(&mut self, u: usize, v: usize) {
-        self.g[u].push(v);
-        self.g[v].push(u);
-    }
-    fn alloc_vid(&mut self) -> usize {
-        let tmp = self.vid;
-        self.vid += 1;
-        tmp
-    }
-    pub fn dfs(&mut self, u: usize) {
-        self.rec(u,self.n);
-    }
-    fn rec(&mut self, u: usize, p: usize) {
-        self.enter[u] = self.alloc_vid();
-        for i in 0..self.g[u].len() {
-            let v = self.g[u][i];
-            if v == p { continue; }
-            self.rec(v,u);
+        EularTour {
+            enter: enter,
+            leave: leave,
         }
-        self.leave[u] = self.alloc_vid();
     }
 }
 #[test]
 fn test_eular_tour() {
-    let mut g = EularTour::new(5);
-    g.connect(0, 1);
-    g.connect(0, 2);
-    g.connect(1, 3);
-    g.connect(1, 4);
-    g.dfs(0);
+    let g = EularTour::new(&Tree::from_edges(5, 0, &[(0, 1, ()), (0, 2, ()), (1, 3, ()), (1, 4, ())]));
     assert_eq!(g.enter, vec![0,1,7,2,4]);
     assert_eq!(g.leave, vec![9,6,8,3,5]);
 }
\ No newline at end of file
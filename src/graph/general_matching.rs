@@ -0,0 +1,234 @@
+use cargo_snippet::snippet;
+use crate::graph::csr::Graph;
+use std::collections::VecDeque;
+
+/// 一般グラフの最大マッチング (Edmondsの花アルゴリズム)
+///
+/// マッチしていない頂点rootからBFSで交互路を伸ばし、増加路を探す。
+/// 二部グラフと違い奇閉路(花)があると交互路の偶奇が崩れるので、
+/// 花を見つけたら一つの頂点(base)に縮約して探索を続ける。
+/// 増加路が見つかったらp(花の中の迂回も含めた親)を辿って反転する。
+/// 増加路が無い頂点からは、その後も増加路は見つからないので、
+/// 各頂点から一回ずつ探せばよい。
+///
+/// gは無向グラフ(両向きに辺を張ったもの)。
+/// マッチした組(u, v) (u < v)をuの昇順で返す。
+///
+/// 計算量 O(V^3)
+
+#[snippet("GeneralMatching")]
+struct Blossom<'a, E> {
+    g: &'a Graph<E>,
+    mt: Vec<Option<usize>>,
+    p: Vec<Option<usize>>,
+    base: Vec<usize>,
+    used: Vec<bool>,
+    blossom: Vec<bool>,
+}
+#[snippet("GeneralMatching")]
+impl<'a, E> Blossom<'a, E> {
+    /// 交互路の木の上での、aとbの花のbaseでのLCA
+    fn lca(&self, a: usize, b: usize) -> usize {
+        let mut seen = vec![false; self.g.n];
+        let mut a = a;
+        loop {
+            a = self.base[a];
+            seen[a] = true;
+            match self.mt[a] {
+                Some(m) => a = self.p[m].unwrap(),
+                None => break,
+            }
+        }
+        let mut b = b;
+        loop {
+            b = self.base[b];
+            if seen[b] {
+                return b;
+            }
+            b = self.p[self.mt[b].unwrap()].unwrap();
+        }
+    }
+    /// vからbaseのbまで、花に含まれる頂点に印をつけ、pを逆向きにも辿れるようにする
+    fn mark_path(&mut self, v: usize, b: usize, child: usize) {
+        let mut v = v;
+        let mut child = child;
+        while self.base[v] != b {
+            let m = self.mt[v].unwrap();
+            self.blossom[self.base[v]] = true;
+            self.blossom[self.base[m]] = true;
+            self.p[v] = Some(child);
+            child = m;
+            v = self.p[m].unwrap();
+        }
+    }
+    /// rootから始まる増加路の終点
+    fn find_path(&mut self, root: usize) -> Option<usize> {
+        let n = self.g.n;
+        for i in 0..n {
+            self.used[i] = false;
+            self.p[i] = None;
+            self.base[i] = i;
+        }
+        self.used[root] = true;
+        let mut q = VecDeque::new();
+        q.push_back(root);
+        while let Some(v) = q.pop_front() {
+            for &(to, _) in self.g.adj(v) {
+                if self.base[v] == self.base[to] || self.mt[v] == Some(to) {
+                    continue;
+                }
+                if to == root || self.mt[to].map_or(false, |m| self.p[m].is_some()) {
+                    // 花を縮約する
+                    let b = self.lca(v, to);
+                    for i in 0..n {
+                        self.blossom[i] = false;
+                    }
+                    self.mark_path(v, b, to);
+                    self.mark_path(to, b, v);
+                    for i in 0..n {
+                        if self.blossom[self.base[i]] {
+                            self.base[i] = b;
+                            if !self.used[i] {
+                                self.used[i] = true;
+                                q.push_back(i);
+                            }
+                        }
+                    }
+                } else if self.p[to].is_none() {
+                    self.p[to] = Some(v);
+                    match self.mt[to] {
+                        None => return Some(to),
+                        Some(m) => {
+                            self.used[m] = true;
+                            q.push_back(m);
+                        },
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[snippet("GeneralMatching")]
+pub fn general_matching<E>(g: &Graph<E>) -> Vec<(usize, usize)> {
+    let n = g.n;
+    let mut b = Blossom {
+        g: g,
+        mt: vec![None; n],
+        p: vec![None; n],
+        base: vec![0; n],
+        used: vec![false; n],
+        blossom: vec![false; n],
+    };
+    // 貪欲に初期解を作っておくと速い
+    for v in 0..n {
+        if b.mt[v].is_some() {
+            continue;
+        }
+        for &(to, _) in g.adj(v) {
+            if to != v && b.mt[to].is_none() {
+                b.mt[v] = Some(to);
+                b.mt[to] = Some(v);
+                break;
+            }
+        }
+    }
+    for root in 0..n {
+        if b.mt[root].is_some() {
+            continue;
+        }
+        if let Some(v) = b.find_path(root) {
+            // 増加路を反転する
+            let mut v = Some(v);
+            while let Some(x) = v {
+                let px = b.p[x].unwrap();
+                let ppx = b.mt[px];
+                b.mt[x] = Some(px);
+                b.mt[px] = Some(x);
+                v = ppx;
+            }
+        }
+    }
+    (0..n).filter_map(|u| match b.mt[u] {
+        Some(v) if u < v => Some((u, v)),
+        _ => None,
+    }).collect()
+}
+
+#[cfg(test)]
+fn check_matching(n: usize, es: &[(usize, usize, ())], matching: &[(usize, usize)]) {
+    let mut used = vec![false; n];
+    for &(u, v) in matching {
+        assert!(es.iter().any(|&(a, b, _)| (a, b) == (u, v) || (a, b) == (v, u)));
+        assert!(!used[u] && !used[v]);
+        used[u] = true;
+        used[v] = true;
+    }
+}
+
+#[test]
+fn test_general_matching() {
+    // 5頂点の奇閉路と、そこから出る辺
+    let es = vec![(0, 1, ()), (1, 2, ()), (2, 3, ()), (3, 4, ()), (4, 0, ()), (0, 5, ())];
+    let g = Graph::from_undirected_edges(6, &es);
+    let m = general_matching(&g);
+    assert_eq!(m.len(), 3);
+    check_matching(6, &es, &m);
+
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..500 {
+        let n = 1 + rand.rand(12) as usize;
+        let k = rand.rand(30) as usize;
+        let es: Vec<(usize, usize, ())> = (0..k).map(|_| (rand.rand(n as u64) as usize, rand.rand(n as u64) as usize, ())).collect();
+        let mut adj = vec![0u32; n];
+        for &(u, v, _) in &es {
+            if u != v {
+                adj[u] |= 1<<v;
+                adj[v] |= 1<<u;
+            }
+        }
+        // dp[mask] = mask内の頂点だけでの最大マッチング
+        let mut dp = vec![0; 1<<n];
+        for mask in 1..1usize<<n {
+            let i = mask.trailing_zeros() as usize;
+            let rest = mask & !(1<<i);
+            let mut best = dp[rest];
+            for j in 0..n {
+                if rest>>j & 1 == 1 && adj[i]>>j & 1 == 1 {
+                    best = std::cmp::max(best, dp[rest & !(1<<j)] + 1);
+                }
+            }
+            dp[mask] = best;
+        }
+        let m = general_matching(&Graph::from_undirected_edges(n, &es));
+        assert_eq!(m.len(), dp[(1<<n)-1]);
+        check_matching(n, &es, &m);
+    }
+}
+
+#[test]
+fn test_general_matching_large() {
+    use crate::graph::bipartite_matching::HopcroftKarp;
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    // 二部グラフならHopcroft-Karpと一致する
+    let half = 500;
+    let mut hk = HopcroftKarp::new(half, half);
+    let mut es = vec![];
+    for _ in 0..1500 {
+        let l = rand.rand(half as u64) as usize;
+        let r = rand.rand(half as u64) as usize;
+        hk.add_edge(l, r);
+        es.push((l, half + r, ()));
+    }
+    let m = general_matching(&Graph::from_undirected_edges(2*half, &es));
+    check_matching(2*half, &es, &m);
+    assert_eq!(m.len(), hk.solve());
+
+    let n = 2000;
+    let es: Vec<(usize, usize, ())> = (0..3000).map(|_| (rand.rand(n as u64) as usize, rand.rand(n as u64) as usize, ())).collect();
+    let m = general_matching(&Graph::from_undirected_edges(n, &es));
+    check_matching(n, &es, &m);
+}
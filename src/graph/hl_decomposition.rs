@@ -1,5 +1,6 @@
 use cargo_snippet::snippet;
 use std::collections::VecDeque;
+use crate::graph::csr::Tree;
 
 /// HL分解は、木構造をパスの集合に分解する。
 /// こうして木構造をvid木上で一直線状にすることにより、
@@ -10,13 +11,10 @@ use std::collections::VecDeque;
 /// 構築 O(N)
 
 #[snippet("HLDecomposition")]
-struct HLDecomposition {
+pub struct HLDecomposition {
     n: usize,
-    g: Vec<Vec<usize>>,
-    subcnt: Vec<usize>,
     depth: Vec<usize>,
     pub par: Vec<Option<usize>>,
-    heavy_next: Vec<Option<usize>>,
     heavy_head: Vec<usize>,
     real_to_virt: Vec<usize>,
     pub virt_to_real: Vec<usize>,
@@ -24,99 +22,61 @@ struct HLDecomposition {
 
 #[snippet("HLDecomposition")]
 impl HLDecomposition {
-    pub fn new(n: usize) -> Self {
-        HLDecomposition {
-            n: n,
-            g: vec![vec![]; n],
-            subcnt: vec![0; n],
-            depth: vec![0; n],
-            par: vec![None; n],
-            heavy_next: vec![None; n],
-            heavy_head: vec![n; n],
-            real_to_virt: vec![n; n],
-            virt_to_real: vec![n; n],
-        }
-    }
-    pub fn connect(&mut self, u: usize, v: usize) {
-        self.g[u].push(v);
-        self.g[v].push(u);
-    }
-
     /// O(N)
-    pub fn build(&mut self, root: usize) {
-        self.dfs1(root);
-        self.dfs2(root);
-        self.bfs(root);
-    }
-
-    // 部分木の大きさを計算する
-    fn dfs1(&mut self, root: usize) {
-        self.depth[root] = 0;
-        self.par[root] = None;
-        self.dfs1_sub(root, None);
-    }
-    fn dfs1_sub(&mut self, u: usize, par: Option<usize>) -> usize {
-        let mut cnt = 1;
-        for v in self.g[u].clone() {
-            if Some(v) == par { continue; }
-            self.depth[v] = self.depth[u] + 1;
-            self.par[v] = Some(u);
-            cnt += self.dfs1_sub(v, Some(u));
+    pub fn new<E>(t: &Tree<E>) -> Self {
+        let n = t.len();
+
+        // 部分木の大きさを計算する
+        let mut subcnt = vec![1; n];
+        for &u in t.order.iter().rev() {
+            if let Some(p) = t.par[u] {
+                subcnt[p] += subcnt[u];
+            }
         }
-        self.subcnt[u] = cnt;
-        cnt
-    }
-    
-    // ヘビーパスを探す
-    fn dfs2(&mut self, root: usize) {
-        self.dfs2_sub(root, None);
-    }
-    fn dfs2_sub(&mut self, u: usize, par: Option<usize>) {
-        let mut maxv = 0;
-        let mut heavy_next = None;
-
-        let cld = self.g[u].clone();
 
         // ヘビーパスを決める
-        for &v in &cld {
-            if Some(v) == par { continue; }
-            if self.subcnt[v] > maxv { 
-                maxv = self.subcnt[v];
-                heavy_next = Some(v);
+        let mut heavy_next = vec![None; n];
+        for u in 0..n {
+            let mut maxv = 0;
+            for &(v, _) in t.children(u) {
+                if subcnt[v] > maxv {
+                    maxv = subcnt[v];
+                    heavy_next[u] = Some(v);
+                }
             }
         }
-        // ヘビーパスがあるならそれを伸ばす
-        if let Some(hn) = heavy_next {
-            self.heavy_next[u] = Some(hn);
-            self.dfs2_sub(hn, Some(u));
-        }
-        // ライトパスはまたルートからやり直し
-        for &v in &cld {
-            if Some(v) == par || Some(v) == heavy_next { continue; }
-            self.dfs2_sub(v, Some(u));
-        }
-    }
 
-    fn bfs(&mut self, root: usize) {
+        let mut heavy_head = vec![n; n];
+        let mut real_to_virt = vec![n; n];
+        let mut virt_to_real = vec![n; n];
         let mut cur_virt_id = 0;
         let mut q = VecDeque::new();
-        q.push_back(root);
+        q.push_back(t.root);
         // ヘビーパスの先頭から下っていく
+        // ライトパスはまたそこを先頭としてやり直し
         while let Some(h) = q.pop_front() {
             let mut cur0 = Some(h);
-            while cur0.is_some() {
-                let cur = cur0.unwrap();
-                self.real_to_virt[cur] = cur_virt_id;
-                self.virt_to_real[cur_virt_id] = cur;
+            while let Some(cur) = cur0 {
+                real_to_virt[cur] = cur_virt_id;
+                virt_to_real[cur_virt_id] = cur;
                 cur_virt_id += 1;
-                self.heavy_head[cur] = h;
-                for v in self.g[cur].clone() {
-                    if Some(v) == self.par[cur] || Some(v) == self.heavy_next[cur] { continue; }
+                heavy_head[cur] = h;
+                for &(v, _) in t.children(cur) {
+                    if Some(v) == heavy_next[cur] { continue; }
                     q.push_back(v);
                 }
-                cur0 = self.heavy_next[cur];
+                cur0 = heavy_next[cur];
             }
         }
+
+        HLDecomposition {
+            n: n,
+            depth: t.depth.clone(),
+            par: t.par.clone(),
+            heavy_head: heavy_head,
+            real_to_virt: real_to_virt,
+            virt_to_real: virt_to_real,
+        }
     }
 
     /// O(log N)
@@ -220,12 +180,9 @@ impl HLDecomposition {
 
 #[test]
 fn test_hl_decomposition() {
-    let mut hl = HLDecomposition::new(13);
     let es = vec![(0,1),(0,2),(0,3),(1,4),(1,5),(8,4),(4,9),(6,2),(6,10),(6,11),(6,12),(3,7)];
-    for (u,v) in es {
-        hl.connect(u,v);
-    }
-    hl.build(0);
+    let es: Vec<_> = es.into_iter().map(|(u,v)| (u,v,())).collect();
+    let hl = HLDecomposition::new(&Tree::from_edges(13, 0, &es));
 
     let lca_test = vec![
         (0,0,0),
@@ -1,52 +1,35 @@
 use cargo_snippet::snippet;
+use crate::graph::csr::Tree;
 
 #[snippet("LCA")]
-struct LCA {
-    tree: Vec<Vec<usize>>,
+pub struct LCA {
     parent: Vec<Vec<Option<usize>>>,
     depth: Vec<usize>,
 }
 #[snippet("LCA")]
 impl LCA {
-    pub fn new(n: usize) -> Self {
+    pub fn new<E>(t: &Tree<E>) -> Self {
+        let n = t.len();
         let mut log_n = (n as f64).log2().ceil() as usize;
         if log_n == 0 {
             log_n = 1;
         }
         assert!(log_n > 0);
-        LCA {
-            tree: vec![vec![];n],
-            parent: vec![vec![None; n]; log_n],
-            depth: vec![0; n],
-        }
-    }
-    pub fn connect(&mut self, u: usize, v: usize) {
-        self.tree[u].push(v);
-        self.tree[v].push(u);
-    }
-    // store direct parent and depth
-    fn dfs(&mut self, u: usize, parent: Option<usize>, depth: usize) {
-        self.parent[0][u] = parent;
-        self.depth[u] = depth;
-        for i in 0 .. self.tree[u].len() {
-            let v = self.tree[u][i];
-            if Some(v) != parent {
-                self.dfs(v, Some(u), depth+1);
-            }
-        }
-    }
-    pub fn build(&mut self, root: usize) {
-        self.dfs(root, None, 0);
-
+        let mut parent = vec![vec![None; n]; log_n];
+        parent[0] = t.par.clone();
         let mut k = 0;
-        while k+1 < self.parent.len() {
-            for u in 0 .. self.tree.len() {
-                if self.parent[k][u].is_some() {
-                    self.parent[k+1][u] = self.parent[k][self.parent[k][u].unwrap()]
+        while k+1 < parent.len() {
+            for u in 0 .. n {
+                if parent[k][u].is_some() {
+                    parent[k+1][u] = parent[k][parent[k][u].unwrap()]
                 } 
             }
             k += 1;
         }
+        LCA {
+            parent: parent,
+            depth: t.depth.clone(),
+        }
     }
     pub fn lca(&self, u: usize, v: usize) -> usize {
         let (mut v0, mut v1) = if self.depth[u] <= self.depth[v] {
@@ -86,25 +69,11 @@ impl LCA {
 
 #[test]
 fn test_lca() {
-    let tree = vec![
-        vec![1,2],
-        vec![0,3,4],
-        vec![0,5],
-        vec![1],
-        vec![1,6,7],
-        vec![2],
-        vec![4],
-        vec![4],
-    ];
-    let mut e = vec![
-        (0,1),(0,2),(1,3),(1,4),(2,5),
-        (4,6),(4,7),
+    let e = vec![
+        (0,1,()),(0,2,()),(1,3,()),(1,4,()),(2,5,()),
+        (4,6,()),(4,7,()),
     ];
-    let mut lca = LCA::new(8);
-    for (u,v) in e {
-        lca.connect(u,v);
-    }
-    lca.build(0);
+    let lca = LCA::new(&Tree::from_edges(8, 0, &e));
 
     let Q = vec![
         (1,2,0,2),
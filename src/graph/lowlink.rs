@@ -1,4 +1,5 @@
 use cargo_snippet::snippet;
+use crate::graph::csr::Graph;
 
 /// 無向グラフにおいて関節点と橋を求める。
 /// 
@@ -21,17 +22,39 @@ use cargo_snippet::snippet;
 /// DFS-Treeのうちu->v辺についてord[u]<low[v]の時、u->vは橋となる。
 /// なぜならば、u->vを切ってしまうとvからuにすら到達出来なくなってしまうため。
 /// 
+/// 二重辺連結成分:
+/// 橋を全て取り除いた時の連結成分。
+/// low[v]==ord[v]となるvは、DFS-Treeの根か、親への辺が橋であるので、
+/// vを帰りがけに見た時に、スタックのv以降を一つの成分とする。
+/// 成分を頂点、橋を辺としたものは森になる(bridge tree)。
+/// 
+/// 二重頂点連結成分(ブロック):
+/// どの一点を取り除いても連結なままの極大な部分グラフ。
+/// uの子vについてord[u]<=low[v]となる時、
+/// スタックのv以降とuが一つのブロックになる。
+/// 孤立点はそれだけで一つのブロックとする。
+/// 頂点とブロックを、所属関係で結んだものは森になる(block-cut tree)。
+/// 
+/// 多重辺を扱うため、親への辺は一本だけ飛ばす。
+/// DFSは自前のスタックで行うので、深いグラフでもスタックオーバーフローしない。
+/// 
 /// 計算量:
 /// 構築 O(V+E)
 
 #[snippet("Lowlink")]
-struct LowLink {
-    g: Vec<Vec<usize>>,
-    used: Vec<bool>,
-    ord: Vec<usize>,
-    low: Vec<usize>,
+pub struct LowLink {
+    pub ord: Vec<usize>,
+    pub low: Vec<usize>,
     pub articulation: Vec<usize>,
     pub bridge: Vec<(usize, usize)>,
+    /// 各頂点が属する二重辺連結成分の番号
+    pub two_edge_comp: Vec<usize>,
+    /// 二重辺連結成分を頂点、橋を辺とした森
+    pub bridge_tree: Graph<()>,
+    /// 各ブロックに属する頂点
+    pub blocks: Vec<Vec<usize>>,
+    /// 頂点0..nが元の頂点、n+iがi番目のブロック
+    pub block_cut_tree: Graph<()>,
 }
 #[snippet("Lowlink")]
 #[doc = "find articulation points and bridges at the same time"]
@@ -43,69 +66,218 @@ impl LowLink {
             (p.1, p.0)
         }
     }
-    pub fn new(n: usize) -> LowLink {
-        let mut g = vec![vec![];n];
-        let mut used = vec![false; n];
-        let mut ord = vec![0; n];
+    /// gは無向グラフ(両向きに辺を張ったもの)
+    pub fn new<E>(g: &Graph<E>) -> LowLink {
+        let n = g.n;
+        let none = std::usize::MAX;
+        let mut ord = vec![none; n];
         let mut low = vec![0; n];
-        let articulation = vec![];
-        let bridge = vec![];
-        LowLink {
-            g,
-            used,
-            ord,
-            low,
-            articulation,
-            bridge,
+        let mut is_articulation = vec![false; n];
+        let mut bridge = vec![];
+        let mut two_edge_comp = vec![0; n];
+        let mut num_comp = 0;
+        let mut blocks = vec![];
+        // 二重辺連結成分用と、ブロック用のスタック
+        let mut estack = vec![];
+        let mut vstack = vec![];
+        let mut k = 0;
+        for s in 0..n {
+            if ord[s] != none {
+                continue;
+            }
+            ord[s] = k;
+            low[s] = k;
+            k += 1;
+            estack.push(s);
+            vstack.push(s);
+            let mut root_children = 0;
+            // (頂点, 親, 次に見る辺の位置, 親への辺を飛ばしたか)
+            let mut call = vec![(s, none, 0, false)];
+            while let Some(&mut (u, par, ref mut i, ref mut skipped)) = call.last_mut() {
+                if let Some(&(v, _)) = g.adj(u).get(*i) {
+                    *i += 1;
+                    if v == par && !*skipped {
+                        *skipped = true;
+                        continue;
+                    }
+                    if ord[v] == none {
+                        ord[v] = k;
+                        low[v] = k;
+                        k += 1;
+                        estack.push(v);
+                        vstack.push(v);
+                        if u == s {
+                            root_children += 1;
+                        }
+                        call.push((v, u, 0, false));
+                    } else {
+                        low[u] = std::cmp::min(low[u], ord[v]);
+                    }
+                    continue;
+                }
+                call.pop();
+                if low[u] == ord[u] {
+                    loop {
+                        let v = estack.pop().unwrap();
+                        two_edge_comp[v] = num_comp;
+                        if v == u {
+                            break;
+                        }
+                    }
+                    num_comp += 1;
+                }
+                if par == none {
+                    if root_children == 0 {
+                        vstack.pop();
+                        blocks.push(vec![u]);
+                    }
+                    is_articulation[u] = root_children > 1;
+                    continue;
+                }
+                low[par] = std::cmp::min(low[par], low[u]);
+                if ord[par] < low[u] {
+                    bridge.push(Self::minmax((par, u)));
+                }
+                if ord[par] <= low[u] {
+                    if par != s {
+                        is_articulation[par] = true;
+                    }
+                    let mut block = vec![par];
+                    loop {
+                        let v = vstack.pop().unwrap();
+                        block.push(v);
+                        if v == u {
+                            break;
+                        }
+                    }
+                    blocks.push(block);
+                }
+            }
+            // 根はブロックを作るたびに残っている
+            vstack.clear();
         }
-    }
-    pub fn connect(&mut self, u: usize, v: usize) {
-        self.g[u].push(v);
-        self.g[v].push(u);
-    }
-    pub fn build(&mut self) {
-        self.do_build(0, 0, None);
-        self.articulation.sort();
-        self.bridge.sort();
-    }
-    fn do_build(&mut self, u: usize, k: usize, par: Option<usize>) -> usize {
-        let mut k = k;
-        self.used[u] = true;
-        self.ord[u] = k;
-        k += 1;
-        self.low[u] = self.ord[u];
-        let mut is_articulation = false;
-        let mut cnt = 0;
-        for i in 0..self.g[u].len() {
-            let v = self.g[u][i];
-            if !self.used[v] {
-               cnt += 1; 
-               k = self.do_build(v, k, Some(u));
-               self.low[u] = std::cmp::min(self.low[u], self.low[v]);
-               is_articulation |= par.is_some() && self.low[v] >= self.ord[u];
-               if self.ord[u] < self.low[v] {
-                   self.bridge.push(Self::minmax((u, v)));
-               }
-            } else if Some(v) != par {
-                self.low[u] = std::cmp::min(self.low[u], self.ord[v]);
-            } else {}
+        bridge.sort();
+
+        let es: Vec<_> = bridge.iter().map(|&(u, v)| (two_edge_comp[u], two_edge_comp[v], ())).collect();
+        let bridge_tree = Graph::from_undirected_edges(num_comp, &es);
+        let mut es = vec![];
+        for (i, block) in blocks.iter().enumerate() {
+            for &v in block {
+                es.push((v, n+i, ()));
+            }
         }
-        is_articulation |= par.is_none() && cnt > 1;
-        if is_articulation {
-            self.articulation.push(u);
+        let block_cut_tree = Graph::from_undirected_edges(n + blocks.len(), &es);
+        LowLink {
+            ord: ord,
+            low: low,
+            articulation: (0..n).filter(|&v| is_articulation[v]).collect(),
+            bridge: bridge,
+            two_edge_comp: two_edge_comp,
+            bridge_tree: bridge_tree,
+            blocks: blocks,
+            block_cut_tree: block_cut_tree,
         }
-        k
     }
 }
 
 #[test]
 fn test_lowlink() {
     let e = vec![(0,1),(0,2),(1,2),(1,3),(2,3),(3,6),(4,6),(5,6),(5,7),(6,7)];
-    let mut g = LowLink::new(8);
-    for (u,v) in e {
-        g.connect(u, v);
-    }
-    g.build();
+    let es: Vec<_> = e.into_iter().map(|(u,v)| (u,v,())).collect();
+    let g = LowLink::new(&Graph::from_undirected_edges(8, &es));
     assert_eq!(g.articulation, [3,6]);
     assert_eq!(g.bridge, [(3,6),(4,6)]);
-}
\ No newline at end of file
+}
+#[cfg(test)]
+fn count_components(n: usize, es: &[(usize, usize)], removed_vertex: Option<usize>) -> usize {
+    use crate::union_find::UnionFind;
+    let mut uf = UnionFind::new(n);
+    for &(u, v) in es {
+        if Some(u) != removed_vertex && Some(v) != removed_vertex {
+            uf.merge(u, v);
+        }
+    }
+    (0..n).filter(|&v| Some(v) != removed_vertex && uf.root(v) == v).count()
+}
+
+#[test]
+fn test_lowlink_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..300 {
+        let n = 1 + rand.rand(10) as usize;
+        let m = rand.rand(15) as usize;
+        let e: Vec<(usize, usize)> = (0..m).map(|_| (rand.rand(n as u64) as usize, rand.rand(n as u64) as usize)).collect();
+        let es: Vec<_> = e.iter().map(|&(u, v)| (u, v, ())).collect();
+        let g = LowLink::new(&Graph::from_undirected_edges(n, &es));
+        let c0 = count_components(n, &e, None);
+
+        // 関節点: 取り除くと成分が増える(孤立点が消える場合を除く)
+        let articulation: Vec<usize> = (0..n).filter(|&v| {
+            let isolated = e.iter().all(|&(a, b)| a == b || a != v && b != v);
+            !isolated && count_components(n, &e, Some(v)) > c0
+        }).collect();
+        assert_eq!(g.articulation, articulation);
+
+        // 橋: 取り除くと成分が増える
+        let mut bridge = vec![];
+        for i in 0..m {
+            let rest: Vec<_> = (0..m).filter(|&j| j != i).map(|j| e[j]).collect();
+            if count_components(n, &rest, None) > c0 {
+                bridge.push(LowLink::minmax(e[i]));
+            }
+        }
+        bridge.sort();
+        assert_eq!(g.bridge, bridge);
+
+        // 二重辺連結成分: 橋を除いた連結成分
+        let rest: Vec<_> = e.iter().cloned().filter(|&p| bridge.binary_search(&LowLink::minmax(p)).is_err()).collect();
+        let mut uf = crate::union_find::UnionFind::new(n);
+        for &(u, v) in &rest {
+            uf.merge(u, v);
+        }
+        for u in 0..n {
+            for v in 0..n {
+                assert_eq!(g.two_edge_comp[u] == g.two_edge_comp[v], uf.same(u, v));
+            }
+        }
+        let num_comp = g.bridge_tree.n;
+        assert_eq!(count_components(num_comp, &bridge.iter().map(|&(u, v)| (g.two_edge_comp[u], g.two_edge_comp[v])).collect::<Vec<_>>(), None), c0);
+
+        // ブロック
+        for block in &g.blocks {
+            let inner: Vec<_> = e.iter().cloned().filter(|&(u, v)| u != v && block.contains(&u) && block.contains(&v)).collect();
+            // ブロック内で連結で、3点以上なら一点を除いても連結
+            assert_eq!(count_components(n, &inner, None), n - block.len() + 1);
+            if block.len() >= 3 {
+                for &x in block {
+                    assert_eq!(count_components(n, &inner, Some(x)), n - block.len() + 1);
+                }
+            }
+        }
+        for &(u, v) in &e {
+            if u != v {
+                assert_eq!(g.blocks.iter().filter(|b| b.contains(&u) && b.contains(&v)).count(), 1);
+            }
+        }
+        for v in 0..n {
+            let nb = g.blocks.iter().filter(|b| b.contains(&v)).count();
+            assert_eq!(g.block_cut_tree.degree(v), nb);
+            // vの周りのブロックの数 = vを除いた時にvの成分が分かれる数
+            let expected = std::cmp::max(1, count_components(n, &e, Some(v)) + 1 - c0);
+            assert_eq!(nb, expected);
+        }
+        assert_eq!(g.block_cut_tree.num_edges() / 2 + c0, g.block_cut_tree.n);
+    }
+}
+
+#[test]
+fn test_lowlink_deep() {
+    let n = 1_000_000;
+    let es: Vec<_> = (0..n-1).map(|i| (i, i+1, ())).collect();
+    let g = LowLink::new(&Graph::from_undirected_edges(n, &es));
+    assert_eq!(g.articulation.len(), n-2);
+    assert_eq!(g.bridge.len(), n-1);
+    assert_eq!(g.bridge_tree.n, n);
+    assert_eq!(g.blocks.len(), n-1);
+}
@@ -99,6 +99,18 @@ fn test_ford_fulkerson() {
     assert_eq!(nw.max_flow(0, 4), 11);
 }
 
+/// Dinic法
+///
+/// sからの残余グラフでのBFSの距離(level)が増える辺だけを使って、
+/// DFSで流せるだけ流すことを繰り返す。
+/// 一回の反復でs-t間の最短路長が真に増えるので、反復はV回以下。
+///
+/// add_edgeは辺の番号を返し、get_edgeで辺ごとの流量が見られる。
+/// change_capで容量を変えた後にmax_flowを呼び直すと、
+/// それまでの流れを活かして続きから流す。
+///
+/// 計算量 O(EV^2)
+
 // verified: GRL_6_A
 #[snippet("dinic")]
 pub mod dinic {
@@ -108,8 +120,17 @@ pub mod dinic {
         cap: i64,
         rev: usize,
     }
+    #[derive(Clone,Copy,Debug,PartialEq,Eq)]
+    pub struct FlowEdge {
+        pub from: usize,
+        pub to: usize,
+        pub cap: i64,
+        pub flow: i64,
+    }
     pub struct Network {
         g: Vec<Vec<Edge>>,
+        /// 辺の番号 -> (from, g[from]での位置)
+        pos: Vec<(usize, usize)>,
         level: Vec<Option<usize>>,
         iter: Vec<usize>,
     }
@@ -118,21 +139,68 @@ pub mod dinic {
         pub fn new(n: usize) -> Network {
             Network {
                 g: vec![vec![]; n],
+                pos: vec![],
                 level: vec![None; n],
                 iter: vec![0; n],
             }
         }
-        pub fn add_edge(&mut self, from: usize, to: usize, cap: i64) {
+        pub fn add_edge(&mut self, from: usize, to: usize, cap: i64) -> usize {
             assert!(cap>=0);
 
-            let from_rev = self.g[to].len();
+            let from_rev = self.g[to].len() + if from == to { 1 } else { 0 };
             let to_rev = self.g[from].len();
+            self.pos.push((from, to_rev));
             self.g[from].push( Edge { to: to, cap: cap, rev: from_rev } );
             self.g[to].push( Edge { to: from, cap: 0, rev: to_rev } );
+            self.pos.len() - 1
         }
         fn n(&self) -> usize {
             self.g.len()
         }
+        pub fn get_edge(&self, id: usize) -> FlowEdge {
+            let (from, i) = self.pos[id];
+            let e = self.g[from][i];
+            let re = self.g[e.to][e.rev];
+            FlowEdge { from: from, to: e.to, cap: e.cap + re.cap, flow: re.cap }
+        }
+        pub fn edges(&self) -> Vec<FlowEdge> {
+            (0..self.pos.len()).map(|id| self.get_edge(id)).collect()
+        }
+        /// 辺idの容量と流量を直接書き換える。流量保存則は呼び出し側が守ること
+        pub fn change_edge(&mut self, id: usize, cap: i64, flow: i64) {
+            assert!(0 <= flow && flow <= cap);
+            let (from, i) = self.pos[id];
+            let e = self.g[from][i];
+            self.g[from][i].cap = cap - flow;
+            self.g[e.to][e.rev].cap = flow;
+        }
+        /// 辺idの容量をcapに変える。
+        /// 流量が容量を超える分は、まず残余グラフで迂回させ、
+        /// 迂回できなかった分はs-t流量を減らして打ち消す。
+        /// 減ったs-t流量を返す。この後max_flow(s,t)を呼ぶと、増やせる分が流れる。
+        pub fn change_cap(&mut self, id: usize, cap: i64, s: usize, t: usize) -> i64 {
+            assert!(cap>=0);
+            let e = self.get_edge(id);
+            if e.flow <= cap {
+                self.change_edge(id, cap, e.flow);
+                return 0;
+            }
+            self.change_edge(id, cap, cap);
+            let mut d = e.flow - cap;
+            if e.from == e.to {
+                return 0;
+            }
+            d -= self.max_flow_with_limit(e.from, e.to, d);
+            if d > 0 {
+                if e.to != t {
+                    assert_eq!(self.max_flow_with_limit(t, e.to, d), d);
+                }
+                if e.from != s {
+                    assert_eq!(self.max_flow_with_limit(e.from, s, d), d);
+                }
+            }
+            d
+        }
         fn bfs(&mut self, s: usize) {
             self.level = vec![None; self.n()];
             let mut q = std::collections::VecDeque::new();
@@ -166,26 +234,323 @@ pub mod dinic {
             }
             return 0;
         }
-        pub fn max_flow(&mut self, s: usize, t: usize) -> i64 {
+        /// 流量がlimitに達したら止める
+        pub fn max_flow_with_limit(&mut self, s: usize, t: usize, limit: i64) -> i64 {
+            assert!(s != t);
             let mut flow = 0;
-            loop {
+            while flow < limit {
                 self.bfs(s);
                 // finally if we could not find any path to t then return flow
                 if self.level[t].is_none() {
-                    return flow;
+                    break;
                 }
 
-                let INF = 2_000_000_001;
                 self.iter = vec![0; self.n()];
-                let mut f = self.dfs(s, t, INF);
-                while f > 0 {
+                loop {
+                    let f = self.dfs(s, t, limit - flow);
+                    if f == 0 {
+                        break;
+                    }
                     flow += f;
-                    f = self.dfs(s, t, INF);
                 }
             }
+            flow
+        }
+        pub fn max_flow(&mut self, s: usize, t: usize) -> i64 {
+            self.max_flow_with_limit(s, t, std::i64::MAX)
+        }
+        /// 最大流を流した後に呼ぶ。
+        /// 残余グラフでsから到達できる頂点(最小カットのs側)ならtrue
+        pub fn min_cut(&self, s: usize) -> Vec<bool> {
+            let mut visited = vec![false; self.n()];
+            let mut stack = vec![s];
+            visited[s] = true;
+            while let Some(v) = stack.pop() {
+                for e in &self.g[v] {
+                    if e.cap > 0 && !visited[e.to] {
+                        visited[e.to] = true;
+                        stack.push(e.to);
+                    }
+                }
+            }
+            visited
+        }
+    }
+
+    /// 最小流量制約付きの最大流
+    ///
+    /// 辺u->vに下限lがあるときは、容量をr-lにして、
+    /// 超頂点Sからvへ、uから超頂点Tへ容量lの辺を張る(まとめて頂点ごとの需要にする)。
+    /// t->sとs->tに容量無限の辺を張り、S->Tの流れが需要を全て満たせば実行可能。
+    /// その後この二本を外して、s->tに流せるだけ流す。
+    /// 下限のせいでs-t流量が負になることもある。
+    ///
+    /// 補助の辺をネットワークに足してしまうので、max_flowは一度だけ呼べる。
+    pub struct LowerBoundFlow {
+        n: usize,
+        nw: Network,
+        lower: Vec<i64>,
+        demand: Vec<i64>,
+        /// max_flowを呼んだか
+        done: bool,
+    }
+    impl LowerBoundFlow {
+        pub fn new(n: usize) -> LowerBoundFlow {
+            LowerBoundFlow {
+                n: n,
+                nw: Network::new(n+2),
+                lower: vec![],
+                demand: vec![0; n],
+                done: false,
+            }
+        }
+        /// 流量を[lower, upper]に制限した辺
+        pub fn add_edge(&mut self, from: usize, to: usize, lower: i64, upper: i64) -> usize {
+            assert!(!self.done);
+            assert!(0 <= lower && lower <= upper);
+            self.demand[to] += lower;
+            self.demand[from] -= lower;
+            self.lower.push(lower);
+            self.nw.add_edge(from, to, upper - lower)
+        }
+        /// 制約を満たす流れが無ければNone
+        pub fn max_flow(&mut self, s: usize, t: usize) -> Option<i64> {
+            assert!(!self.done, "LowerBoundFlow::max_flow can be called only once");
+            self.done = true;
+            let (ss, tt) = (self.n, self.n+1);
+            let mut need = 0;
+            for v in 0..self.n {
+                if self.demand[v] > 0 {
+                    self.nw.add_edge(ss, v, self.demand[v]);
+                    need += self.demand[v];
+                } else if self.demand[v] < 0 {
+                    self.nw.add_edge(v, tt, -self.demand[v]);
+                }
+            }
+            let back = self.nw.add_edge(t, s, std::i64::MAX);
+            let forward = self.nw.add_edge(s, t, std::i64::MAX);
+            if self.nw.max_flow(ss, tt) != need {
+                return None;
+            }
+            let f = self.nw.get_edge(back).flow - self.nw.get_edge(forward).flow;
+            self.nw.change_edge(back, 0, 0);
+            self.nw.change_edge(forward, 0, 0);
+            Some(f + self.nw.max_flow(s, t))
+        }
+        /// 辺idの流量(下限込み)
+        pub fn flow(&self, id: usize) -> i64 {
+            self.nw.get_edge(id).flow + self.lower[id]
         }
     }
 }
+
+/// Highest-Label Preflow-Push (HLPP)
+///
+/// 各頂点に高さhを持ち、余剰のある頂点のうち一番高いものから、
+/// 高さが一つ低い隣へ押し出す。押し出せなくなったら高さを上げる(relabel)。
+/// 高さは、tへ届く頂点はtへの距離、届かない頂点はn+sへの距離が下限で、
+/// 時々BFSで下限まで上げる(global relabel)。
+/// ある高さ(<n)の頂点が居なくなったら、それより上でn未満の頂点はtへ届かないので
+/// 一気にn+1まで上げる(gap)。
+/// 最後は余剰が全てsに戻るので、辺ごとの流量もそのまま正しい流れになる。
+///
+/// 計算量 O(V^2 √E)
+
+#[snippet("hlpp")]
+pub mod hlpp {
+    #[derive(Clone,Copy,Debug)]
+    struct Edge {
+        to: usize,
+        cap: i64,
+        rev: usize,
+    }
+    #[derive(Clone,Copy,Debug,PartialEq,Eq)]
+    pub struct FlowEdge {
+        pub from: usize,
+        pub to: usize,
+        pub cap: i64,
+        pub flow: i64,
+    }
+    pub struct Network {
+        g: Vec<Vec<Edge>>,
+        pos: Vec<(usize, usize)>,
+    }
+    impl Network {
+        pub fn new(n: usize) -> Network {
+            Network {
+                g: vec![vec![]; n],
+                pos: vec![],
+            }
+        }
+        pub fn add_edge(&mut self, from: usize, to: usize, cap: i64) -> usize {
+            assert!(cap>=0);
+            let from_rev = self.g[to].len() + if from == to { 1 } else { 0 };
+            let to_rev = self.g[from].len();
+            self.pos.push((from, to_rev));
+            self.g[from].push( Edge { to: to, cap: cap, rev: from_rev } );
+            self.g[to].push( Edge { to: from, cap: 0, rev: to_rev } );
+            self.pos.len() - 1
+        }
+        fn n(&self) -> usize {
+            self.g.len()
+        }
+        pub fn get_edge(&self, id: usize) -> FlowEdge {
+            let (from, i) = self.pos[id];
+            let e = self.g[from][i];
+            let re = self.g[e.to][e.rev];
+            FlowEdge { from: from, to: e.to, cap: e.cap + re.cap, flow: re.cap }
+        }
+        pub fn edges(&self) -> Vec<FlowEdge> {
+            (0..self.pos.len()).map(|id| self.get_edge(id)).collect()
+        }
+        /// 残余グラフを逆向きにBFSして、rootの高さ+距離を高さにする
+        fn bfs_height(&self, root: usize, h: &mut Vec<usize>) {
+            let mut q = std::collections::VecDeque::new();
+            q.push_back(root);
+            while let Some(v) = q.pop_front() {
+                for e in &self.g[v] {
+                    if self.g[e.to][e.rev].cap > 0 && h[e.to] == 2*self.n() {
+                        h[e.to] = h[v] + 1;
+                        q.push_back(e.to);
+                    }
+                }
+            }
+        }
+        fn global_relabel(&self, s: usize, t: usize, h: &mut Vec<usize>) {
+            let n = self.n();
+            for x in h.iter_mut() {
+                *x = 2*n;
+            }
+            h[t] = 0;
+            h[s] = n;
+            self.bfs_height(t, h);
+            self.bfs_height(s, h);
+        }
+        pub fn max_flow(&mut self, s: usize, t: usize) -> i64 {
+            assert!(s != t);
+            let n = self.n();
+            let mut h = vec![0; n];
+            let mut ex = vec![0; n];
+            let mut cur = vec![0; n];
+            for i in 0..self.g[s].len() {
+                let e = self.g[s][i];
+                if e.cap > 0 {
+                    self.g[s][i].cap = 0;
+                    self.g[e.to][e.rev].cap += e.cap;
+                    ex[e.to] += e.cap;
+                    ex[s] -= e.cap;
+                }
+            }
+            let mut buckets: Vec<Vec<usize>> = vec![vec![]; 2*n+1];
+            let mut cnt = vec![0; n];
+            let mut highest = 0;
+            let mut work = std::usize::MAX;
+            loop {
+                if work > 4*n + self.pos.len() {
+                    work = 0;
+                    self.global_relabel(s, t, &mut h);
+                    for b in buckets.iter_mut() {
+                        b.clear();
+                    }
+                    for c in cnt.iter_mut() {
+                        *c = 0;
+                    }
+                    for v in 0..n {
+                        cur[v] = 0;
+                        if h[v] < n {
+                            cnt[h[v]] += 1;
+                        }
+                        if v != s && v != t && ex[v] > 0 {
+                            buckets[h[v]].push(v);
+                            highest = std::cmp::max(highest, h[v]);
+                        }
+                    }
+                }
+                while highest > 0 && buckets[highest].is_empty() {
+                    highest -= 1;
+                }
+                let v = match buckets[highest].pop() {
+                    Some(v) => v,
+                    None => break,
+                };
+                if h[v] != highest || ex[v] == 0 {
+                    continue;
+                }
+                // discharge
+                loop {
+                    if cur[v] == self.g[v].len() {
+                        // relabel
+                        work += self.g[v].len();
+                        let old = h[v];
+                        let mut nh = 2*n;
+                        for e in &self.g[v] {
+                            if e.cap > 0 {
+                                nh = std::cmp::min(nh, h[e.to] + 1);
+                            }
+                        }
+                        h[v] = nh;
+                        cur[v] = 0;
+                        if old < n {
+                            cnt[old] -= 1;
+                        }
+                        if nh < n {
+                            cnt[nh] += 1;
+                        }
+                        if old < n && cnt[old] == 0 {
+                            // gap
+                            for w in 0..n {
+                                if old < h[w] && h[w] < n {
+                                    cnt[h[w]] -= 1;
+                                    h[w] = n+1;
+                                    cur[w] = 0;
+                                    if w != s && w != t && ex[w] > 0 && w != v {
+                                        buckets[h[w]].push(w);
+                                    }
+                                }
+                            }
+                        }
+                        buckets[h[v]].push(v);
+                        highest = std::cmp::max(highest, h[v]);
+                        break;
+                    }
+                    let e = self.g[v][cur[v]];
+                    if e.cap > 0 && h[v] == h[e.to] + 1 {
+                        let d = std::cmp::min(ex[v], e.cap);
+                        self.g[v][cur[v]].cap -= d;
+                        self.g[e.to][e.rev].cap += d;
+                        ex[v] -= d;
+                        if e.to != s && e.to != t && ex[e.to] == 0 {
+                            buckets[h[e.to]].push(e.to);
+                        }
+                        ex[e.to] += d;
+                        if ex[v] == 0 {
+                            break;
+                        }
+                    }
+                    cur[v] += 1;
+                }
+            }
+            ex[t]
+        }
+        /// 最大流を流した後に呼ぶ。
+        /// 残余グラフでsから到達できる頂点(最小カットのs側)ならtrue
+        pub fn min_cut(&self, s: usize) -> Vec<bool> {
+            let mut visited = vec![false; self.n()];
+            let mut stack = vec![s];
+            visited[s] = true;
+            while let Some(v) = stack.pop() {
+                for e in &self.g[v] {
+                    if e.cap > 0 && !visited[e.to] {
+                        visited[e.to] = true;
+                        stack.push(e.to);
+                    }
+                }
+            }
+            visited
+        }
+    }
+}
+
 #[test]
 fn test_dinic() {
     use dinic::*;
@@ -206,4 +571,215 @@ fn test_dinic() {
     }
 
     assert_eq!(nw.max_flow(0, 4), 11);
-}
\ No newline at end of file
+    assert_eq!(nw.min_cut(0), [true, true, true, false, false]);
+    // 3->4の容量を下げると、迂回できない分だけ流量が減る
+    assert_eq!(nw.change_cap(6, 4, 0, 4), 2);
+    assert_eq!(nw.max_flow(0, 4), 0);
+    // 戻すと続きから流せる
+    assert_eq!(nw.change_cap(6, 8, 0, 4), 0);
+    assert_eq!(nw.max_flow(0, 4), 2);
+    assert_eq!(nw.get_edge(6), dinic::FlowEdge { from: 3, to: 4, cap: 8, flow: 6 });
+}
+
+#[test]
+fn test_hlpp() {
+    use hlpp::*;
+    let mut nw = Network::new(5);
+    let conns = [
+        (0, 1, 10),
+        (0, 2, 2),
+        (1, 2, 6),
+        (1, 3, 6),
+        (3, 2, 3),
+        (2, 4, 5),
+        (3, 4, 8),
+    ];
+    for conn in &conns {
+        nw.add_edge(conn.0, conn.1, conn.2);
+    }
+    assert_eq!(nw.max_flow(0, 4), 11);
+    assert_eq!(nw.min_cut(0), [true, true, true, false, false]);
+}
+
+#[cfg(test)]
+fn check_flow(n: usize, s: usize, t: usize, f: i64, es: &[(usize, usize, i64, i64)]) {
+    // (from, to, cap, flow) が容量制約と流量保存則を満たすこと
+    let mut bal = vec![0; n];
+    for &(u, v, c, x) in es {
+        assert!(0 <= x && x <= c);
+        bal[u] -= x;
+        bal[v] += x;
+    }
+    for v in 0..n {
+        let expected = if v == s { -f } else if v == t { f } else { 0 };
+        assert_eq!(bal[v], expected);
+    }
+}
+
+#[test]
+fn test_max_flow_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..300 {
+        let n = 2 + rand.rand(8) as usize;
+        let m = rand.rand(25) as usize;
+        let es: Vec<(usize, usize, i64)> = (0..m).map(|_| (rand.rand(n as u64) as usize, rand.rand(n as u64) as usize, rand.rand(10) as i64)).collect();
+        let (s, t) = (0, n-1);
+        // 最小カットを全探索
+        let mut best = std::i64::MAX;
+        for mask in 0..1u32<<n {
+            if mask & 1 == 0 || mask>>(n-1) & 1 == 1 {
+                continue;
+            }
+            let c = es.iter().filter(|&&(u, v, _)| mask>>u & 1 == 1 && mask>>v & 1 == 0).map(|e| e.2).sum();
+            best = std::cmp::min(best, c);
+        }
+
+        let mut fnw = ford_fulkerson::Network::new(n);
+        let mut dnw = dinic::Network::new(n);
+        let mut hnw = hlpp::Network::new(n);
+        for &(u, v, c) in &es {
+            fnw.add_edge(u, v, c);
+            dnw.add_edge(u, v, c);
+            hnw.add_edge(u, v, c);
+        }
+        assert_eq!(fnw.max_flow(s, t), best);
+        assert_eq!(dnw.max_flow(s, t), best);
+        assert_eq!(hnw.max_flow(s, t), best);
+
+        check_flow(n, s, t, best, &dnw.edges().iter().map(|e| (e.from, e.to, e.cap, e.flow)).collect::<Vec<_>>());
+        check_flow(n, s, t, best, &hnw.edges().iter().map(|e| (e.from, e.to, e.cap, e.flow)).collect::<Vec<_>>());
+        for cut in &[dnw.min_cut(s), hnw.min_cut(s)] {
+            assert!(cut[s] && !cut[t]);
+            let c: i64 = es.iter().filter(|&&(u, v, _)| cut[u] && !cut[v]).map(|e| e.2).sum();
+            assert_eq!(c, best);
+        }
+
+        // 容量を変えて流し直した結果が、最初から作り直した結果と一致すること
+        let mut es = es;
+        let mut flow = best;
+        for _ in 0..3 {
+            if m == 0 {
+                break;
+            }
+            let id = rand.rand(m as u64) as usize;
+            let c = rand.rand(10) as i64;
+            es[id].2 = c;
+            flow -= dnw.change_cap(id, c, s, t);
+            flow += dnw.max_flow(s, t);
+            let mut fresh = dinic::Network::new(n);
+            for &(u, v, c) in &es {
+                fresh.add_edge(u, v, c);
+            }
+            assert_eq!(flow, fresh.max_flow(s, t));
+            check_flow(n, s, t, flow, &dnw.edges().iter().map(|e| (e.from, e.to, e.cap, e.flow)).collect::<Vec<_>>());
+        }
+    }
+}
+
+#[test]
+fn test_lower_bound_flow() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..300 {
+        let n = 2 + rand.rand(4) as usize;
+        let m = rand.rand(6) as usize;
+        let es: Vec<(usize, usize, i64, i64)> = (0..m).map(|_| {
+            let l = rand.rand(3) as i64;
+            (rand.rand(n as u64) as usize, rand.rand(n as u64) as usize, l, l + rand.rand(3) as i64)
+        }).collect();
+        let (s, t) = (0, n-1);
+        let mut lb = dinic::LowerBoundFlow::new(n);
+        for &(u, v, l, r) in &es {
+            lb.add_edge(u, v, l, r);
+        }
+        let res = lb.max_flow(s, t);
+
+        // 各辺の流量を全探索
+        let mut best = None;
+        let mut x: Vec<i64> = es.iter().map(|e| e.2).collect();
+        loop {
+            let mut bal = vec![0; n];
+            for i in 0..m {
+                bal[es[i].0] -= x[i];
+                bal[es[i].1] += x[i];
+            }
+            if (0..n).all(|v| v == s || v == t || bal[v] == 0) {
+                best = std::cmp::max(best, Some(bal[t]));
+            }
+            let mut i = 0;
+            while i < m && x[i] == es[i].3 {
+                x[i] = es[i].2;
+                i += 1;
+            }
+            if i == m {
+                break;
+            }
+            x[i] += 1;
+        }
+        assert_eq!(res, best);
+        if res.is_some() {
+            let mut bal = vec![0; n];
+            for i in 0..m {
+                let f = lb.flow(i);
+                assert!(es[i].2 <= f && f <= es[i].3);
+                bal[es[i].0] -= f;
+                bal[es[i].1] += f;
+            }
+            for v in 1..n-1 {
+                assert_eq!(bal[v], 0);
+            }
+            assert_eq!(Some(bal[t]), res);
+        }
+    }
+}
+
+#[should_panic(expected = "only once")]
+#[test]
+fn test_lower_bound_flow_twice() {
+    let mut lb = dinic::LowerBoundFlow::new(3);
+    lb.add_edge(0, 1, 1, 2);
+    lb.add_edge(1, 2, 0, 3);
+    assert_eq!(lb.max_flow(0, 2), Some(2));
+    lb.max_flow(0, 2);
+}
+
+#[cfg(test)]
+fn bench_network(n: usize, m: usize) -> Vec<(usize, usize, i64)> {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    (0..m).map(|_| (rand.rand(n as u64) as usize, rand.rand(n as u64) as usize, 1 + rand.rand(1000) as i64)).collect()
+}
+#[bench]
+fn bench_ford_fulkerson(b: &mut test::Bencher) {
+    let es = bench_network(1000, 10000);
+    b.iter(|| {
+        let mut nw = ford_fulkerson::Network::new(1000);
+        for &(u, v, c) in &es {
+            nw.add_edge(u, v, c);
+        }
+        nw.max_flow(0, 999)
+    })
+}
+#[bench]
+fn bench_dinic(b: &mut test::Bencher) {
+    let es = bench_network(1000, 10000);
+    b.iter(|| {
+        let mut nw = dinic::Network::new(1000);
+        for &(u, v, c) in &es {
+            nw.add_edge(u, v, c);
+        }
+        nw.max_flow(0, 999)
+    })
+}
+#[bench]
+fn bench_hlpp(b: &mut test::Bencher) {
+    let es = bench_network(1000, 10000);
+    b.iter(|| {
+        let mut nw = hlpp::Network::new(1000);
+        for &(u, v, c) in &es {
+            nw.add_edge(u, v, c);
+        }
+        nw.max_flow(0, 999)
+    })
+}
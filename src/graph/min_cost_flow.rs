@@ -1,3 +1,5 @@
+use cargo_snippet::snippet;
+
 // verified: GRL_6_B
 mod bellman_ford {
     #[derive(Clone,Copy,Debug)]
@@ -8,7 +10,7 @@ mod bellman_ford {
         rev: usize,
     }
 
-    struct Network {
+    pub struct Network {
         g: Vec<Vec<Edge>>,
     }
 
@@ -251,4 +253,378 @@ mod dijkstra {
         }
         assert_eq!(g.min_cost_flow(0,5,3), Some(18));
     }
-}
\ No newline at end of file
+}
+
+/// 主双対法 (primal-dual)
+///
+/// ポテンシャルhで辺のコストを cost + h[u] - h[v] >= 0 に直し、
+/// ダイクストラ法で最短路を求めて流すことを繰り返す。
+/// 負のコストの辺がある時は、最初にベルマンフォード法でhを求める。
+/// ただしsから到達できる負閉路があってはいけない。
+///
+/// slope:
+/// 流量に対する最小コストは下に凸な折れ線になる。
+/// その折れ点(流量, コスト)を(0, 0)から順に返す。
+///
+/// min_cost_circulation:
+/// 負のコストの辺を全て飽和させてから、
+/// 生じた過不足を超頂点を使って最小コストで打ち消す。
+/// 負閉路があっても良い。
+///
+/// 計算量:
+/// slope O(F E logV) (負の辺がある時は+O(VE))
+
+#[snippet("primal_dual")]
+pub mod primal_dual {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    #[derive(Clone,Copy,Debug)]
+    struct Edge {
+        to: usize,
+        cap: i64,
+        cost: i64,
+        rev: usize,
+    }
+    #[derive(Clone,Copy,Debug,PartialEq,Eq)]
+    pub struct FlowEdge {
+        pub from: usize,
+        pub to: usize,
+        pub cap: i64,
+        pub flow: i64,
+        pub cost: i64,
+    }
+    pub struct Network {
+        g: Vec<Vec<Edge>>,
+        /// 辺の番号 -> (from, g[from]での位置)
+        pos: Vec<(usize, usize)>,
+    }
+    impl Network {
+        pub fn new(n: usize) -> Network {
+            Network {
+                g: vec![vec![]; n],
+                pos: vec![],
+            }
+        }
+        fn n(&self) -> usize {
+            self.g.len()
+        }
+        /// costは負でも良い
+        pub fn add_edge(&mut self, from: usize, to: usize, cap: i64, cost: i64) -> usize {
+            assert!(cap>=0);
+            let from_rev = self.g[to].len() + if from == to { 1 } else { 0 };
+            let to_rev = self.g[from].len();
+            self.pos.push((from, to_rev));
+            self.g[from].push(Edge { to: to, cap: cap, cost: cost, rev: from_rev });
+            self.g[to].push(Edge { to: from, cap: 0, cost: -cost, rev: to_rev });
+            self.pos.len() - 1
+        }
+        pub fn get_edge(&self, id: usize) -> FlowEdge {
+            let (from, i) = self.pos[id];
+            let e = self.g[from][i];
+            let re = self.g[e.to][e.rev];
+            FlowEdge { from: from, to: e.to, cap: e.cap + re.cap, flow: re.cap, cost: e.cost }
+        }
+        pub fn edges(&self) -> Vec<FlowEdge> {
+            (0..self.pos.len()).map(|id| self.get_edge(id)).collect()
+        }
+        /// 残余グラフに負のコストの辺があれば、ベルマンフォード法でsからの距離を求める
+        fn initial_potential(&self, s: usize) -> Vec<i64> {
+            let n = self.n();
+            let mut h = vec![0; n];
+            let negative = self.g.iter().any(|es| es.iter().any(|e| e.cap > 0 && e.cost < 0));
+            if !negative {
+                return h;
+            }
+            let mut dist = vec![None; n];
+            dist[s] = Some(0);
+            for k in 0..n+1 {
+                let mut update = false;
+                for v in 0..n {
+                    let d = match dist[v] {
+                        Some(d) => d,
+                        None => continue,
+                    };
+                    for e in &self.g[v] {
+                        if e.cap > 0 && dist[e.to].map_or(true, |x| d + e.cost < x) {
+                            dist[e.to] = Some(d + e.cost);
+                            update = true;
+                        }
+                    }
+                }
+                if !update {
+                    break;
+                }
+                assert!(k < n, "negative cycle");
+            }
+            for v in 0..n {
+                if let Some(d) = dist[v] {
+                    h[v] = d;
+                }
+            }
+            h
+        }
+        /// 流量limitまで流した時の、流量とコストの折れ線
+        pub fn slope(&mut self, s: usize, t: usize, limit: i64) -> Vec<(i64, i64)> {
+            assert!(s != t);
+            let n = self.n();
+            let mut h = self.initial_potential(s);
+            let mut prevv = vec![0; n];
+            let mut preve = vec![0; n];
+            let mut res = vec![(0, 0)];
+            let mut flow = 0;
+            let mut cost = 0;
+            let mut prev_d = None;
+            while flow < limit {
+                let mut dist: Vec<Option<i64>> = vec![None; n];
+                let mut heap = BinaryHeap::new();
+                dist[s] = Some(0);
+                heap.push(Reverse((0, s)));
+                while let Some(Reverse((d, v))) = heap.pop() {
+                    if dist[v] != Some(d) {
+                        continue;
+                    }
+                    for (i, e) in self.g[v].iter().enumerate() {
+                        if e.cap == 0 {
+                            continue;
+                        }
+                        let nd = d + e.cost + h[v] - h[e.to];
+                        if dist[e.to].map_or(true, |x| nd < x) {
+                            dist[e.to] = Some(nd);
+                            prevv[e.to] = v;
+                            preve[e.to] = i;
+                            heap.push(Reverse((nd, e.to)));
+                        }
+                    }
+                }
+                if dist[t].is_none() {
+                    break;
+                }
+                for v in 0..n {
+                    if let Some(d) = dist[v] {
+                        h[v] += d;
+                    }
+                }
+                // 一単位あたりのコスト
+                let d = h[t] - h[s];
+                let mut f = limit - flow;
+                let mut v = t;
+                while v != s {
+                    f = std::cmp::min(f, self.g[prevv[v]][preve[v]].cap);
+                    v = prevv[v];
+                }
+                let mut v = t;
+                while v != s {
+                    let e = self.g[prevv[v]][preve[v]];
+                    self.g[prevv[v]][preve[v]].cap -= f;
+                    self.g[v][e.rev].cap += f;
+                    v = prevv[v];
+                }
+                flow += f;
+                cost += f * d;
+                // 傾きが同じなら折れ点をまとめる
+                if prev_d == Some(d) {
+                    res.pop();
+                }
+                res.push((flow, cost));
+                prev_d = Some(d);
+            }
+            res
+        }
+        /// 流量fを流した時の最小コスト。流しきれなければNone
+        pub fn min_cost_flow(&mut self, s: usize, t: usize, f: i64) -> Option<i64> {
+            let &(flow, cost) = self.slope(s, t, f).last().unwrap();
+            if flow == f {
+                Some(cost)
+            } else {
+                None
+            }
+        }
+        /// (最大流量, その時の最小コスト)
+        pub fn min_cost_max_flow(&mut self, s: usize, t: usize) -> (i64, i64) {
+            *self.slope(s, t, std::i64::MAX).last().unwrap()
+        }
+        /// 最小費用循環流のコスト。各辺の流量はget_edgeで見る
+        pub fn min_cost_circulation(&mut self) -> i64 {
+            let n = self.n();
+            let m = self.pos.len();
+            let mut ex = vec![0; n];
+            let mut cost = 0;
+            for v in 0..n {
+                for i in 0..self.g[v].len() {
+                    let e = self.g[v][i];
+                    if e.cap > 0 && e.cost < 0 {
+                        self.g[v][i].cap = 0;
+                        self.g[e.to][e.rev].cap += e.cap;
+                        ex[v] -= e.cap;
+                        ex[e.to] += e.cap;
+                        cost += e.cap * e.cost;
+                    }
+                }
+            }
+            let (ss, tt) = (n, n+1);
+            self.g.push(vec![]);
+            self.g.push(vec![]);
+            let mut need = 0;
+            for v in 0..n {
+                if ex[v] > 0 {
+                    self.add_edge(ss, v, ex[v], 0);
+                    need += ex[v];
+                } else if ex[v] < 0 {
+                    self.add_edge(v, tt, -ex[v], 0);
+                }
+            }
+            let (flow, c) = *self.slope(ss, tt, need).last().unwrap();
+            assert_eq!(flow, need);
+            // 超頂点への辺は各頂点の辺の最後にある
+            for v in 0..n {
+                if ex[v] != 0 {
+                    self.g[v].pop();
+                }
+            }
+            self.g.truncate(n);
+            self.pos.truncate(m);
+            cost + c
+        }
+    }
+}
+
+#[test]
+fn test_primal_dual() {
+    use primal_dual::*;
+    let mut g = Network::new(5);
+    g.add_edge(0, 1, 10, 2);
+    g.add_edge(1, 3, 6, 2);
+    g.add_edge(1, 2, 6, 6);
+    g.add_edge(0, 2, 2, 4);
+    g.add_edge(3, 2, 3, 3);
+    g.add_edge(2, 4, 5, 2);
+    g.add_edge(3, 4, 8, 6);
+    assert_eq!(g.min_cost_flow(0, 4, 9), Some(80));
+
+    let mut g = Network::new(3);
+    g.add_edge(0, 1, 2, 1);
+    g.add_edge(0, 1, 3, 2);
+    g.add_edge(1, 2, 10, 0);
+    assert_eq!(g.slope(0, 2, 10), [(0, 0), (2, 2), (5, 8)]);
+    assert_eq!(g.get_edge(1), primal_dual::FlowEdge { from: 0, to: 1, cap: 3, flow: 3, cost: 2 });
+}
+
+#[test]
+fn test_primal_dual_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..200 {
+        let n = 2 + rand.rand(6) as usize;
+        let m = rand.rand(15) as usize;
+        // 負のコストも入れるので、閉路ができないようにu<vの辺だけにする
+        let es: Vec<(usize, usize, i64, i64)> = (0..m).filter_map(|_| {
+            let u = rand.rand(n as u64) as usize;
+            let v = rand.rand(n as u64) as usize;
+            let cap = rand.rand(5) as i64;
+            let cost = rand.rand(11) as i64 - 5;
+            if u < v { Some((u, v, cap, cost)) } else { None }
+        }).collect();
+        let (s, t) = (0, n-1);
+        let mut g = primal_dual::Network::new(n);
+        for &(u, v, cap, cost) in &es {
+            g.add_edge(u, v, cap, cost);
+        }
+        let sl = g.slope(s, t, std::i64::MAX);
+        let &(max_flow, total) = sl.last().unwrap();
+
+        // 各流量について、ベルマンフォード版で作り直して比べる
+        for f in 0..max_flow+1 {
+            let mut bf = bellman_ford::Network::new(n);
+            for &(u, v, cap, cost) in &es {
+                bf.add_edge(u, v, cap, cost);
+            }
+            let expected = bf.min_cost_flow(s, t, f).unwrap();
+            let k = sl.iter().position(|p| p.0 >= f).unwrap();
+            let cost = if sl[k].0 == f {
+                sl[k].1
+            } else {
+                let (f0, c0) = sl[k-1];
+                let (f1, c1) = sl[k];
+                c0 + (c1 - c0) / (f1 - f0) * (f - f0)
+            };
+            assert_eq!(cost, expected);
+        }
+        let mut bf = bellman_ford::Network::new(n);
+        for &(u, v, cap, _) in &es {
+            bf.add_edge(u, v, cap, 0);
+        }
+        assert_eq!(bf.min_cost_flow(s, t, max_flow+1), None);
+        // 折れ点は凸
+        for w in sl.windows(3) {
+            assert!((w[1].1 - w[0].1) * (w[2].0 - w[1].0) < (w[2].1 - w[1].1) * (w[1].0 - w[0].0));
+        }
+
+        let mut bal = vec![0; n];
+        let mut c = 0;
+        for e in g.edges() {
+            assert!(0 <= e.flow && e.flow <= e.cap);
+            bal[e.from] -= e.flow;
+            bal[e.to] += e.flow;
+            c += e.flow * e.cost;
+        }
+        assert_eq!(c, total);
+        for v in 1..n-1 {
+            assert_eq!(bal[v], 0);
+        }
+        assert_eq!(bal[t], max_flow);
+    }
+}
+
+#[test]
+fn test_min_cost_circulation() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..300 {
+        let n = 1 + rand.rand(4) as usize;
+        let m = rand.rand(6) as usize;
+        let es: Vec<(usize, usize, i64, i64)> = (0..m).map(|_| {
+            (rand.rand(n as u64) as usize, rand.rand(n as u64) as usize, rand.rand(3) as i64, rand.rand(11) as i64 - 5)
+        }).collect();
+        let mut g = primal_dual::Network::new(n);
+        for &(u, v, cap, cost) in &es {
+            g.add_edge(u, v, cap, cost);
+        }
+        let res = g.min_cost_circulation();
+
+        // 各辺の流量を全探索
+        let mut best = 0;
+        let mut x = vec![0; m];
+        loop {
+            let mut bal = vec![0; n];
+            for i in 0..m {
+                bal[es[i].0] -= x[i];
+                bal[es[i].1] += x[i];
+            }
+            if bal.iter().all(|&b| b == 0) {
+                best = std::cmp::min(best, (0..m).map(|i| x[i] * es[i].3).sum());
+            }
+            let mut i = 0;
+            while i < m && x[i] == es[i].2 {
+                x[i] = 0;
+                i += 1;
+            }
+            if i == m {
+                break;
+            }
+            x[i] += 1;
+        }
+        assert_eq!(res, best);
+
+        let mut bal = vec![0; n];
+        let mut c = 0;
+        for e in g.edges() {
+            assert!(0 <= e.flow && e.flow <= e.cap);
+            bal[e.from] -= e.flow;
+            bal[e.to] += e.flow;
+            c += e.flow * e.cost;
+        }
+        assert_eq!(c, res);
+        assert!(bal.iter().all(|&b| b == 0));
+    }
+}
@@ -15,4 +15,8 @@ pub mod zenho;
 pub mod dijkstra_queue;
 pub mod adjacency_matrix;
 pub mod centroid;
-pub mod euler_tour;
\ No newline at end of file
+pub mod euler_tour;
+pub mod csr;
+pub mod dijkstra;
+pub mod two_sat;
+pub mod general_matching;
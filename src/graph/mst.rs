@@ -94,6 +94,7 @@ mod kraskal {
 #[snippet("chu_liu_edmonds")]
 mod chu_liu_edmonds {
     use crate::graph::scc::SCC;
+    use crate::graph::csr::Graph;
 
     #[derive(Debug,Clone,Copy)]
     pub struct Edge(pub usize, pub u64);
@@ -123,14 +124,14 @@ mod chu_liu_edmonds {
             min_out_g[e.0].push(to);
         }
 
-        let mut scc = SCC::new(n);
+        let mut es = vec![];
         for u in 0..n {
             for i in 0..min_out_g[u].len() {
                 let v = min_out_g[u][i];
-                scc.add_edge(u, v);
+                es.push((u, v, ()));
             }
         }
-        scc.build();
+        let scc = SCC::new(&Graph::from_edges(n, &es));
 
         // dbg!(&min_in_g);
         // dbg!(&min_out_g);
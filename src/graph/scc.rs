@@ -1,76 +1,110 @@
 use cargo_snippet::snippet;
-use std::collections::VecDeque;
+use crate::graph::csr::Graph;
 
-/// 強連結分解
+/// 強連結分解 (Tarjan)
 /// 
 /// 有向グラフをお互いに行き来可能な
 /// 頂点集合に分解する。
 /// 
 /// 入力: 有向グラフ
-/// 出力: 各ノードが所属する集合
+/// 出力: 各ノードが所属する集合と、縮約したDAG
+/// 
+/// DFSの行きがけ順ordと、DFS木の辺と、スタックに残っている頂点への辺を使って
+/// 到達できるordの最小値lowを求める。
+/// low[v] == ord[v] となったvが成分の根で、スタックのv以降が一つの成分になる。
+/// 成分はトポロジカル順の逆に見つかる。
+/// 再帰は自前のスタックで行うので、深いグラフでもスタックオーバーフローしない。
 /// 
 /// 計算量: O(V+E)
 
 #[snippet("SCC")]
+#[snippet("TwoSat")]
 pub struct SCC {
-    g: Vec<Vec<usize>>,
-    r_g: Vec<Vec<usize>>,
-    post_order: VecDeque<usize>,
-    used: Vec<bool>,
+    /// 各頂点が属する成分の番号。トポロジカル順に振られる
     pub order: Vec<usize>,
+    /// 各成分に属する頂点
+    pub groups: Vec<Vec<usize>>,
+    /// 成分を縮約したDAG。辺は番号の小さい成分から大きい成分へ向かう
+    pub dag: Graph<()>,
 }
 
 #[snippet("SCC")]
+#[snippet("TwoSat")]
 impl SCC {
-    pub fn new(n: usize) -> Self {
-        Self {
-            g: vec![vec![];n],
-            r_g: vec![vec![];n],
-            post_order: VecDeque::new(),
-            used: vec![false; n],
-            order: vec![n; n],
-        }
-    }
-    pub fn add_edge(&mut self, u: usize, v: usize) {
-        self.g[u].push(v);
-        self.r_g[v].push(u);
-    }
-    fn dfs(&mut self, u: usize) {
-        self.used[u] = true;
-        for i in 0 .. self.g[u].len() {
-            let v = self.g[u][i];
-            if !self.used[v] {
-                self.dfs(v);
+    pub fn new<E>(g: &Graph<E>) -> Self {
+        let n = g.n;
+        let none = std::usize::MAX;
+        let mut ord = vec![none; n];
+        let mut low = vec![0; n];
+        let mut comp = vec![none; n];
+        let mut stack = vec![];
+        let mut k = 0;
+        let mut t = 0;
+        for s in 0..n {
+            if ord[s] != none {
+                continue;
             }
-        }
-        self.post_order.push_front(u);
-    }
-    fn rdfs(&mut self, u: usize, k: usize) {
-        self.used[u] = true;
-        self.order[u] = k;
-        for i in 0 .. self.r_g[u].len() {
-            let v = self.r_g[u][i];
-            if !self.used[v] {
-                self.rdfs(v, k);
+            ord[s] = t;
+            low[s] = t;
+            t += 1;
+            stack.push(s);
+            // (頂点, 次に見る辺の位置)
+            let mut call = vec![(s, 0)];
+            while let Some(&mut (u, ref mut i)) = call.last_mut() {
+                if let Some(&(v, _)) = g.adj(u).get(*i) {
+                    *i += 1;
+                    if ord[v] == none {
+                        ord[v] = t;
+                        low[v] = t;
+                        t += 1;
+                        stack.push(v);
+                        call.push((v, 0));
+                    } else if comp[v] == none {
+                        low[u] = std::cmp::min(low[u], ord[v]);
+                    }
+                } else {
+                    call.pop();
+                    if let Some(&(p, _)) = call.last() {
+                        low[p] = std::cmp::min(low[p], low[u]);
+                    }
+                    if low[u] == ord[u] {
+                        loop {
+                            let v = stack.pop().unwrap();
+                            comp[v] = k;
+                            if v == u {
+                                break;
+                            }
+                        }
+                        k += 1;
+                    }
+                }
             }
         }
-    }
-    pub fn build(&mut self) {
-        for v in 0 .. self.g.len() {
-            if !self.used[v] {
-                self.dfs(v);
-            }
+
+        let order: Vec<usize> = comp.iter().map(|&c| k-1-c).collect();
+        let mut groups = vec![vec![]; k];
+        for v in 0..n {
+            groups[order[v]].push(v);
         }
-        // dbg!(&self.post_order);
-        self.used = vec![false; self.g.len()];
-        let mut k = 0;
-        for i in 0 .. self.post_order.len() {
-            let v = self.post_order[i];
-            if !self.used[v] {
-                self.rdfs(v, k);
-                k += 1;
+        let mut es = vec![];
+        for u in 0..n {
+            for &(v, _) in g.adj(u) {
+                if order[u] != order[v] {
+                    es.push((order[u], order[v], ()));
+                }
             }
         }
+        es.sort();
+        es.dedup();
+        SCC {
+            order: order,
+            groups: groups,
+            dag: Graph::from_edges(k, &es),
+        }
+    }
+    /// 成分の数
+    pub fn len(&self) -> usize {
+        self.groups.len()
     }
 }
 
@@ -91,13 +125,69 @@ fn test_scc() {
         vec![],
     ];
     let n = e.len();
-    let mut g = SCC::new(n);
+    let mut es = vec![];
     for u in 0..n {
         for v in e[u].clone() {
-            g.add_edge(u,v);
+            es.push((u,v,()));
         }
     }
-    g.build();
+    let g = SCC::new(&Graph::from_edges(n, &es));
 
     assert_eq!(g.order, [0,1,2,2,2,3,3,3,4,6,6,5]);
-}
\ No newline at end of file
+}
+#[test]
+fn test_scc_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..100 {
+        let n = 1 + rand.rand(15) as usize;
+        let m = rand.rand(30) as usize;
+        let es: Vec<(usize, usize, ())> = (0..m).map(|_| (rand.rand(n as u64) as usize, rand.rand(n as u64) as usize, ())).collect();
+        let scc = SCC::new(&Graph::from_edges(n, &es));
+        // 推移閉包
+        let mut reach = vec![vec![false; n]; n];
+        for v in 0..n {
+            reach[v][v] = true;
+        }
+        for &(u, v, _) in &es {
+            reach[u][v] = true;
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    if reach[i][k] && reach[k][j] {
+                        reach[i][j] = true;
+                    }
+                }
+            }
+        }
+        for u in 0..n {
+            for v in 0..n {
+                assert_eq!(scc.order[u] == scc.order[v], reach[u][v] && reach[v][u]);
+            }
+        }
+        for &(u, v, _) in &es {
+            assert!(scc.order[u] <= scc.order[v]);
+        }
+        for c in 0..scc.len() {
+            assert!(!scc.groups[c].is_empty());
+            for &v in &scc.groups[c] {
+                assert_eq!(scc.order[v], c);
+            }
+            for &(d, _) in scc.dag.adj(c) {
+                assert!(c < d);
+                assert!(es.iter().any(|&(u, v, _)| scc.order[u] == c && scc.order[v] == d));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_scc_deep() {
+    // 長い一本のサイクル
+    let n = 1_000_000;
+    let es: Vec<(usize, usize, ())> = (0..n).map(|i| (i, (i+1) % n, ())).collect();
+    let scc = SCC::new(&Graph::from_edges(n, &es));
+    assert_eq!(scc.len(), 1);
+    assert_eq!(scc.dag.num_edges(), 0);
+}
@@ -7,52 +7,38 @@ use cargo_snippet::snippet;
 
 #[snippet("tree_diameter")]
 pub mod tree_diameter {
-    #[derive(Clone, Copy)]
-    struct Edge {
-        dst: usize,
-        weight: i64,
-    }
-    pub struct FindFurthestPair {
-        g: Vec<Vec<Edge>>
-    }
-    impl FindFurthestPair {
-        pub fn find(&self, v: usize) -> (usize, i64) {
-            self.find_rec(None, v)
-        }
-        fn find_rec(&self, par: Option<usize>, v: usize) -> (usize, i64) {
-            let mut r = (v, 0);
-            for &e in &self.g[v] {
-                if Some(e.dst) != par {
-                    let mut t = self.find_rec(Some(v), e.dst);
-                    t.1 += e.weight;
-                    if r.1 < t.1 {
-                        r = t;
-                    }
+    use crate::graph::csr::{Graph, Tree};
+
+    /// vから一番遠い頂点とその距離
+    fn find_furthest(g: &Graph<i64>, v: usize) -> (usize, i64) {
+        let mut r = (v, 0);
+        let mut stack = vec![(v, g.n, 0)];
+        while let Some((u, par, d)) = stack.pop() {
+            if r.1 < d {
+                r = (u, d);
+            }
+            for &(w, weight) in g.adj(u) {
+                if w != par {
+                    stack.push((w, u, d + weight));
                 }
             }
-            r
         }
+        r
     }
-    pub struct TreeDiameter {
-        g: Vec<Vec<Edge>>,
+    /// (端点, 端点, 直径)
+    pub fn tree_diameter(t: &Tree<i64>) -> (usize, usize, i64) {
+        let (v, _) = find_furthest(&t.g, t.root);
+        let (w, d) = find_furthest(&t.g, v);
+        (v, w, d)
     }
-    impl TreeDiameter {
-        pub fn new(n: usize) -> TreeDiameter {
-            TreeDiameter {
-                g: vec![vec![];n]
-            }
-        }
-        pub fn connect(&mut self, u: usize, v: usize, dist: i64) {
-            self.g[u].push(Edge { dst: v, weight: dist });
-            self.g[v].push(Edge { dst: u, weight: dist });
-        }
-        pub fn solve(&self) -> (usize, usize, i64) {
-            let ffp = FindFurthestPair {
-                g: self.g.clone(),
-            };
-            let (v, _) = ffp.find(0);
-            let (w, d) = ffp.find(v);
-            (v, w, d)
-        }
-    }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_tree_diameter() {
+    use crate::graph::csr::Tree;
+    use self::tree_diameter::tree_diameter;
+    let t = Tree::from_edges(6, 0, &[(0, 1, 3), (1, 2, 4), (1, 3, 1), (3, 4, 5), (0, 5, 2)]);
+    let (v, w, d) = tree_diameter(&t);
+    assert_eq!(d, 11);
+    assert_eq!((v.min(w), v.max(w)), (4, 5));
+}
@@ -1,5 +1,6 @@
 use cargo_snippet::snippet;
 use std::collections::VecDeque;
+use crate::graph::csr::Graph;
 
 // 有向グラフにおいて
 // どの頂点も、その出力辺の先の頂点より先に来るように順序付けることを
@@ -19,64 +20,49 @@ use std::collections::VecDeque;
 // 構築 O(V+E)
 
 #[snippet("TopologicalSort")]
-struct TopologicalSort {
-    g: Vec<Vec<usize>>,
-    colors: Vec<bool>,
-    indeg: Vec<u32>,
-    Q: VecDeque<usize>,
-    out: Vec<usize>,
+pub struct TopologicalSort {
+    pub out: Vec<usize>,
 }
 
 #[snippet("TopologicalSort")]
 impl TopologicalSort {
-    pub fn new(n: usize) -> Self {
-        let colors = vec![false; n];
-        let indeg = vec![0; n];
-        TopologicalSort {
-            g: vec![vec![];n],
-            Q: VecDeque::new(),
-            colors: colors,
-            indeg: indeg,
-            out: Vec::new(),
-        }
-    }
-    pub fn add_edge(&mut self, u: usize, v: usize) {
-        self.g[u].push(v);
-    }
-    fn bfs(&mut self, s: usize) {
-        self.Q.push_back(s);
-        self.colors[s] = true;
-        while !self.Q.is_empty() {
-            let u = self.Q.pop_front().unwrap();
-            self.out.push(u);
-            for &v in &self.g[u] {
-                self.indeg[v] -= 1;
-                if self.indeg[v] == 0 && self.colors[v] == false {
-                    self.colors[v] = true;
-                    self.Q.push_back(v);
-                }
-            }
-        }
-    }
-    pub fn tsort(&mut self) {
-        let n = self.g.len();
+    pub fn new<E>(g: &Graph<E>) -> Self {
+        let n = g.n;
+        let mut indeg = vec![0; n];
         for u in 0..n {
-            let conn = &self.g[u];
-            for &next in conn {
-                self.indeg[next] += 1;
+            for &(v, _) in g.adj(u) {
+                indeg[v] += 1;
             }
         }
-        for u in 0..n {
-            if self.indeg[u] == 0 && self.colors[u] == false {
-                self.bfs(u)
+        let mut colors = vec![false; n];
+        let mut out = Vec::new();
+        let mut q = VecDeque::new();
+        for s in 0..n {
+            if indeg[s] != 0 || colors[s] {
+                continue;
+            }
+            q.push_back(s);
+            colors[s] = true;
+            while let Some(u) = q.pop_front() {
+                out.push(u);
+                for &(v, _) in g.adj(u) {
+                    indeg[v] -= 1;
+                    if indeg[v] == 0 && !colors[v] {
+                        colors[v] = true;
+                        q.push_back(v);
+                    }
+                }
             }
         }
+        TopologicalSort {
+            out: out,
+        }
     }
 }
 
 #[test]
 fn test_tsort() {
-    let mut e = vec![
+    let e = vec![
         vec![1],
         vec![2],
         vec![],
@@ -85,12 +71,12 @@ fn test_tsort() {
         vec![2],
     ];
     let n = e.len();
-    let mut g = TopologicalSort::new(n);
+    let mut es = vec![];
     for u in 0..n {
         for v in e[u].clone() {
-            g.add_edge(u, v);
+            es.push((u, v, ()));
         }
     }
-    g.tsort();
+    let g = TopologicalSort::new(&Graph::from_edges(n, &es));
     assert_eq!(g.out, [0,3,1,4,5,2]);
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+use cargo_snippet::snippet;
+use crate::graph::csr::Graph;
+use crate::graph::scc::SCC;
+
+/// 2-SAT
+///
+/// (x_i == f) ∨ (x_j == g) という節の論理積を満たす割り当てを求める。
+/// 各変数について「真」「偽」の2頂点を作り、
+/// 節 a ∨ b を含意 ¬a → b と ¬b → a の辺にする。
+/// 強連結分解して、x_iの真と偽が同じ成分にあれば充足不能。
+/// そうでなければ、トポロジカル順で後ろにある方を採用すればよい。
+///
+/// 計算量 O(N+M)
+
+#[snippet("TwoSat")]
+pub struct TwoSat {
+    n: usize,
+    es: Vec<(usize, usize, ())>,
+    answer: Vec<bool>,
+}
+#[snippet("TwoSat")]
+impl TwoSat {
+    pub fn new(n: usize) -> TwoSat {
+        TwoSat {
+            n: n,
+            es: vec![],
+            answer: vec![false; n],
+        }
+    }
+    /// x_i == f という条件を表す頂点
+    fn lit(i: usize, f: bool) -> usize {
+        2*i + if f { 0 } else { 1 }
+    }
+    /// (x_i == f) ∨ (x_j == g)
+    pub fn add_clause(&mut self, i: usize, f: bool, j: usize, g: bool) {
+        let a = Self::lit(i, f);
+        let b = Self::lit(j, g);
+        self.es.push((a^1, b, ()));
+        self.es.push((b^1, a, ()));
+    }
+    pub fn satisfiable(&mut self) -> bool {
+        let scc = SCC::new(&Graph::from_edges(2*self.n, &self.es));
+        for i in 0..self.n {
+            let t = scc.order[2*i];
+            let f = scc.order[2*i+1];
+            if t == f {
+                return false;
+            }
+            self.answer[i] = t > f;
+        }
+        true
+    }
+    /// satisfiableがtrueを返した後に呼ぶ
+    pub fn assignment(&self) -> &[bool] {
+        &self.answer
+    }
+}
+
+#[test]
+fn test_two_sat() {
+    let mut ts = TwoSat::new(2);
+    // x0 ∨ x1, ¬x0 ∨ x1, ¬x1 ∨ ¬x0
+    ts.add_clause(0, true, 1, true);
+    ts.add_clause(0, false, 1, true);
+    ts.add_clause(1, false, 0, false);
+    assert!(ts.satisfiable());
+    assert_eq!(ts.assignment(), [false, true]);
+    // x1 ∨ x1 と ¬x1 ∨ ¬x1 は両立しない
+    ts.add_clause(1, false, 1, false);
+    assert!(!ts.satisfiable());
+}
+
+#[test]
+fn test_two_sat_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..300 {
+        let n = 1 + rand.rand(8) as usize;
+        let m = rand.rand(20) as usize;
+        let mut ts = TwoSat::new(n);
+        let mut clauses = vec![];
+        for _ in 0..m {
+            let c = (rand.rand(n as u64) as usize, rand.rand(2) == 0, rand.rand(n as u64) as usize, rand.rand(2) == 0);
+            ts.add_clause(c.0, c.1, c.2, c.3);
+            clauses.push(c);
+        }
+        let ok = |x: &[bool]| clauses.iter().all(|&(i, f, j, g)| x[i] == f || x[j] == g);
+        let brute = (0..1u32<<n).any(|mask| {
+            let x: Vec<bool> = (0..n).map(|i| mask>>i & 1 == 1).collect();
+            ok(&x)
+        });
+        assert_eq!(ts.satisfiable(), brute);
+        if brute {
+            assert!(ok(ts.assignment()));
+        }
+    }
+}
@@ -20,6 +20,7 @@ pub mod convex_hull_trick;
 pub mod coord_compression;
 pub mod cumsum;
 pub mod doubling;
+pub mod dynamic_seg;
 pub mod fenwick;
 pub mod garner;
 pub mod gaussian_elimination;
@@ -35,8 +36,10 @@ pub mod matrix;
 pub mod modint;
 pub mod number;
 pub mod permutation;
+pub mod persistent_seg;
 pub mod polynomial;
 pub mod prime;
+pub mod reduction;
 pub mod rev;
 pub mod seg_node;
 pub mod seg;
@@ -1,18 +1,36 @@
 use cargo_snippet::snippet;
 
+/// 行列
+///
+/// 要素はi64の他、ModIntのような環であればよい。
+/// i64の場合はmul_rem/powで法を指定する。
+
 #[snippet("Matrix")]
 pub mod matrix {
     #[derive(Clone, PartialEq, Debug)]
-    pub struct Matrix {
-        pub v: Vec<Vec<i64>>,
+    pub struct Matrix<T = i64> {
+        pub v: Vec<Vec<T>>,
     }
-    impl Matrix {
-        pub fn new(v: Vec<Vec<i64>>) -> Self {
+    impl<T> Matrix<T> {
+        pub fn new(v: Vec<Vec<T>>) -> Self {
             Self { v }
         }
-        pub fn into_inner(self) -> Vec<Vec<i64>> {
+        pub fn into_inner(self) -> Vec<Vec<T>> {
             self.v
         }
+        pub fn m(&self) -> usize {
+            self.v.len()
+        }
+        pub fn n(&self) -> usize {
+            self.v[0].len()
+        }
+    }
+    impl<T> Matrix<T>
+    where T: Copy + Default + From<i64> +
+             std::ops::Add<Output = T> +
+             std::ops::Sub<Output = T> +
+             std::ops::Mul<Output = T>
+    {
         /// (a|b)
         pub fn combine(a: Self, b: Self) -> Self {
             assert_eq!(a.m(), b.m());
@@ -38,22 +56,33 @@ pub mod matrix {
             ret
         }
         pub fn zeros(m: usize, n: usize) -> Self {
-            let v = vec![vec![0;n];m];
+            let v = vec![vec![T::default();n];m];
             Self { v }
         }
         pub fn identity(n: usize) -> Self {
-            let mut v = vec![vec![0;n];n];
+            let mut v = vec![vec![T::default();n];n];
             for i in 0..n {
-                v[i][i] = 1;
+                v[i][i] = T::from(1);
             }
             Matrix { v: v }
         }
-        pub fn m(&self) -> usize {
-            self.v.len()
-        }
-        pub fn n(&self) -> usize {
-            self.v[0].len()
+        /// 要素の演算そのものが法を取る場合(ModIntなど)の累乗
+        pub fn pow_ring(&self, k: u64) -> Self {
+            assert!(self.m() == self.n());
+            let mut k = k;
+            let mut x = Self::identity(self.m());
+            let mut y = self.clone();
+            while k > 0 {
+                if k & 1 > 0 {
+                    x = y.clone() * x;
+                }
+                y = y.clone() * y;
+                k >>= 1;
+            }
+            x
         }
+    }
+    impl Matrix<i64> {
         pub fn mul_rem(&self, other: &Self, mo: i64) -> Self {
             assert!(self.n() == other.m());
             let K = self.n();
@@ -92,43 +121,43 @@ pub mod matrix {
 
     use std::ops::*;
 
-    impl Add for Matrix {
+    impl<T: Copy + Add<Output = T>> Add for Matrix<T> {
         type Output = Self;
         fn add(self, other: Self) -> Self {
             let mut r = self.v.clone();
             for i in 0..self.m() {
                 for j in 0..self.n() {
-                    r[i][j] += other.v[i][j];
+                    r[i][j] = r[i][j] + other.v[i][j];
                 }
             }
             Matrix { v: r }
         }
     }
-    impl Sub for Matrix {
+    impl<T: Copy + Sub<Output = T>> Sub for Matrix<T> {
         type Output = Self;
         fn sub(self, other: Self) -> Self {
             let mut r = self.v.clone();
             for i in 0..self.m() {
                 for j in 0..self.n() {
-                    r[i][j] -= other.v[i][j];
+                    r[i][j] = r[i][j] - other.v[i][j];
                 }
             }
             Matrix { v: r }
         }
     }
-    impl Mul for Matrix {
+    impl<T: Copy + Default + Add<Output = T> + Mul<Output = T>> Mul for Matrix<T> {
         type Output = Self;
         fn mul(self, other: Self) -> Self {
             assert!(self.n() == other.m());
             let K = self.n();
             let M = self.m();
             let N = other.n();
-            let mut r = vec![vec![0; N]; M];
+            let mut r = vec![vec![T::default(); N]; M];
             for i in 0..M {
                 for j in 0..N {
-                    let mut v = 0;
+                    let mut v = T::default();
                     for k in 0..K {
-                        v += self.v[i][k] * other.v[k][j];
+                        v = v + self.v[i][k] * other.v[k][j];
                     }
                     r[i][j] = v;
                 }
@@ -136,7 +165,7 @@ pub mod matrix {
             Matrix { v: r }
         }
     }
-    impl Rem<i64> for Matrix {
+    impl Rem<i64> for Matrix<i64> {
         type Output = Self;
         fn rem(self, mo: i64) -> Self {
             let mut r = self.v.clone();
@@ -148,7 +177,7 @@ pub mod matrix {
             Matrix { v: r }
         }
     }
-    impl RemAssign<i64> for Matrix {
+    impl RemAssign<i64> for Matrix<i64> {
         fn rem_assign(&mut self, mo: i64) {
             for i in 0..self.m() {
                 for j in 0..self.n() {
@@ -157,13 +186,13 @@ pub mod matrix {
             }
         }
     }
-    impl std::ops::Index<usize> for Matrix {
-        type Output = [i64];
+    impl<T> std::ops::Index<usize> for Matrix<T> {
+        type Output = [T];
         fn index(&self, i: usize) -> &Self::Output {
             &self.v[i]
         }
     }
-    impl std::ops::IndexMut<usize> for Matrix {
+    impl<T> std::ops::IndexMut<usize> for Matrix<T> {
         fn index_mut(&mut self, i: usize) -> &mut Self::Output {
             &mut self.v[i]
         }
@@ -243,4 +272,15 @@ fn test_matrix_pow() {
     };
     let x3 = x.clone() * x.clone() * x.clone();
     assert_eq!(x.pow(3, std::i64::MAX).v, x3.v);
-}
\ No newline at end of file
+}
+#[test]
+fn test_matrix_modint_pow() {
+    use crate::modint::modular::Mod998;
+    let fib = matrix::Matrix::new(vec![
+        vec![Mod998::new(1), Mod998::new(1)],
+        vec![Mod998::new(1), Mod998::new(0)],
+    ]);
+    let x = Matrix::new(vec![vec![1,1],vec![1,0]]);
+    assert_eq!(fib.pow_ring(1000)[0][1].0, x.pow(1000, 998_244_353)[0][1]);
+    assert_eq!(fib.pow_ring(10)[0][1], Mod998::new(55));
+}
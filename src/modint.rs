@@ -1,4 +1,5 @@
 use cargo_snippet::snippet;
+use crate::reduction::{Barrett, Reduction};
 
 /// モジュラ逆元
 /// 
@@ -12,22 +13,81 @@ use cargo_snippet::snippet;
 /// a a^(p-2) = 1 (mod p)
 /// がいえる。
 /// これよりa^(p-2)はaの逆元であることがいえる。
+/// 
+/// 法はModulusトレイトで与える。
+/// コンパイル時定数ならConstModulus<P>、
+/// 実行時に読み込むならDynModulus::set(p)を使う。
+/// DynModulusの掛け算は除算命令を使わないBarrett reductionで行う。
+/// 64bit除算が速いCPUでは素朴な%と変わらないか遅いこともあるので、
+/// bench_modint_dyn と bench_modint_dyn_naive で確かめること。
 
 #[snippet("modint")]
+#[snippet("ModComb")]
 pub mod modular {
-    const M: i64 = 1_000_000_007;
+    use std::marker::PhantomData;
+    use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+    use super::{Barrett, Reduction};
+
+    pub trait Modulus: Copy + Clone + Default + ::std::fmt::Debug + PartialEq + Eq + PartialOrd + Ord {
+        fn modulus() -> i64;
+        /// a*b mod m (0 <= a,b < m)
+        /// mは2^32未満なので、積はu64に収まる
+        fn mul(a: i64, b: i64) -> i64 {
+            (a as u64 * b as u64 % Self::modulus() as u64) as i64
+        }
+    }
+    #[derive(Debug, Clone, Copy, Default, PartialOrd, Ord, PartialEq, Eq)]
+    pub struct ConstModulus<const P: u32>;
+    impl<const P: u32> Modulus for ConstModulus<P> {
+        fn modulus() -> i64 {
+            P as i64
+        }
+    }
+    static DYN_MODULUS: AtomicU32 = AtomicU32::new(1_000_000_007);
+    static DYN_IM: AtomicU64 = AtomicU64::new(Barrett::new(1_000_000_007).im);
+    #[derive(Debug, Clone, Copy, Default, PartialOrd, Ord, PartialEq, Eq)]
+    pub struct DynModulus;
+    impl DynModulus {
+        /// 既に作ったDynModの値は新しい法の下では意味を持たない
+        pub fn set(p: u32) {
+            DYN_MODULUS.store(p, Ordering::Relaxed);
+            DYN_IM.store(Barrett::new(p).im, Ordering::Relaxed);
+        }
+    }
+    impl Modulus for DynModulus {
+        fn modulus() -> i64 {
+            DYN_MODULUS.load(Ordering::Relaxed) as i64
+        }
+        fn mul(a: i64, b: i64) -> i64 {
+            let barrett = Barrett {
+                m: DYN_MODULUS.load(Ordering::Relaxed),
+                im: DYN_IM.load(Ordering::Relaxed),
+            };
+            barrett.mul_plain(a as u32, b as u32) as i64
+        }
+    }
     #[derive(Debug, Clone, Copy, Default, PartialOrd, Ord, PartialEq, Eq)]
-    pub struct Mod(pub i64);
-    impl ::std::fmt::Display for Mod {
+    pub struct ModInt<M: Modulus>(pub i64, PhantomData<M>);
+    pub type ModP<const P: u32> = ModInt<ConstModulus<P>>;
+    pub type Mod998 = ModP<998_244_353>;
+    pub type Mod1e9_7 = ModP<1_000_000_007>;
+    pub type DynMod = ModInt<DynModulus>;
+    pub type Mod = Mod1e9_7;
+
+    impl<M: Modulus> ::std::fmt::Display for ModInt<M> {
         fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
             write!(f, "{}", self.0)
         }
     }
-    impl Mod {
-        pub fn new(v: i64) -> Mod {
-            Mod((v+M) % M)
+    impl<M: Modulus> ModInt<M> {
+        pub fn new(v: i64) -> ModInt<M> {
+            let m = M::modulus();
+            ModInt((v % m + m) % m, PhantomData)
         }
-        pub fn pow(self, mut r: i64) -> Mod {
+        pub fn modulus() -> i64 {
+            M::modulus()
+        }
+        pub fn pow(self, mut r: i64) -> ModInt<M> {
             let mut k = self;
             let mut ret = 1.into();
             while r > 0 {
@@ -40,67 +100,152 @@ pub mod modular {
             ret
         }
         // This requires M is prime
-        pub fn recip(self) -> Mod {
-            self.pow(M - 2)
+        pub fn recip(self) -> ModInt<M> {
+            self.pow(M::modulus() - 2)
         }
     }
     use std::ops::*;
-    impl<T: Into<Mod>> Add<T> for Mod {
-        type Output = Mod;
+    impl<M: Modulus, T: Into<ModInt<M>>> Add<T> for ModInt<M> {
+        type Output = ModInt<M>;
         fn add(self, rhs: T) -> Self::Output {
-            Mod::new(self.0 + rhs.into().0)
+            ModInt::new(self.0 + rhs.into().0)
         }
     }
-    impl<T: Into<Mod>> AddAssign<T> for Mod {
+    impl<M: Modulus, T: Into<ModInt<M>>> AddAssign<T> for ModInt<M> {
         fn add_assign(&mut self, rhs: T) {
             *self = *self + rhs;
         }
     }
-    impl<T: Into<Mod>> Sub<T> for Mod {
-        type Output = Mod;
+    impl<M: Modulus, T: Into<ModInt<M>>> Sub<T> for ModInt<M> {
+        type Output = ModInt<M>;
         fn sub(self, rhs: T) -> Self::Output {
-            Mod::new(self.0 - rhs.into().0 + M)
+            ModInt::new(self.0 - rhs.into().0 + M::modulus())
         }
     }
-    impl<T: Into<Mod>> SubAssign<T> for Mod {
+    impl<M: Modulus, T: Into<ModInt<M>>> SubAssign<T> for ModInt<M> {
         fn sub_assign(&mut self, rhs: T) {
             *self = *self - rhs;
         }
     }
-    impl<T: Into<Mod>> Mul<T> for Mod {
-        type Output = Mod;
+    impl<M: Modulus, T: Into<ModInt<M>>> Mul<T> for ModInt<M> {
+        type Output = ModInt<M>;
         fn mul(self, rhs: T) -> Self::Output {
-            Mod::new(self.0 * rhs.into().0)
+            ModInt(M::mul(self.0, rhs.into().0), PhantomData)
         }
     }
-    impl<T: Into<Mod>> MulAssign<T> for Mod {
+    impl<M: Modulus, T: Into<ModInt<M>>> MulAssign<T> for ModInt<M> {
         fn mul_assign(&mut self, rhs: T) {
             *self = *self * rhs;
         }
     }
-    impl<T: Into<Mod>> Div<T> for Mod {
-        type Output = Mod;
+    impl<M: Modulus, T: Into<ModInt<M>>> Div<T> for ModInt<M> {
+        type Output = ModInt<M>;
         fn div(self, rhs: T) -> Self::Output {
             self * rhs.into().recip()
         }
     }
-    impl<T: Into<Mod>> DivAssign<T> for Mod {
+    impl<M: Modulus, T: Into<ModInt<M>>> DivAssign<T> for ModInt<M> {
         fn div_assign(&mut self, rhs: T) {
             *self = *self / rhs;
         }
     }
-    impl Neg for Mod {
-        type Output = Mod;
+    impl<M: Modulus> Neg for ModInt<M> {
+        type Output = ModInt<M>;
         fn neg(self) -> Self::Output {
-            Mod(0) - self
+            ModInt::new(0) - self
         }
     }
-    impl<T: ::std::convert::Into<i64>> ::std::convert::From<T> for Mod {
+    impl<M: Modulus, T: ::std::convert::Into<i64>> ::std::convert::From<T> for ModInt<M> {
         fn from(v: T) -> Self {
-            Mod::new(v.into())
+            ModInt::new(v.into())
         }
     }
 }
 
 #[snippet("modint")]
-pub type Mod = modular::Mod;
\ No newline at end of file
+pub type Mod = modular::Mod;
+
+#[test]
+fn test_modint_const_modulus() {
+    use modular::{Mod998, Mod1e9_7};
+    let a: Mod998 = 998_244_352.into();
+    assert_eq!(a + 1, 0.into());
+    assert_eq!(-Mod998::new(1), a);
+    let b: Mod1e9_7 = 2.into();
+    assert_eq!(b.pow(30), 73741817.into());
+    assert_eq!(b * b.recip(), 1.into());
+    assert_eq!(Mod998::new(3) / 3, 1.into());
+    assert_eq!(Mod::new(-1).0, 1_000_000_006);
+
+    // 2^32未満の最大の素数でも積が溢れない
+    type ModBig = modular::ModP<4_294_967_291>;
+    let c = ModBig::new(-1);
+    assert_eq!(c.0, 4_294_967_290);
+    assert_eq!(c * c, 1.into());
+    assert_eq!(c.recip(), c);
+    assert_eq!(c + c, c - 1);
+}
+
+#[test]
+fn test_modint_dyn_modulus() {
+    use modular::{DynMod, DynModulus};
+    DynModulus::set(13);
+    let a: DynMod = 20.into();
+    assert_eq!(a.0, 7);
+    assert_eq!(a * 2, 1.into());
+    assert_eq!(a.recip(), 2.into());
+    assert_eq!(DynMod::modulus(), 13);
+
+    // 2^31以上の法
+    DynModulus::set(4_294_967_291);
+    let b = DynMod::new(-2);
+    assert_eq!(b * b, 4.into());
+    assert_eq!(b * b.recip(), 1.into());
+}
+
+#[cfg(test)]
+const BENCH_N: i64 = 1_000_000;
+
+/// 比較用: 実行時の法でそのまま%を取る
+#[cfg(test)]
+static NAIVE_DYN_MODULUS: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(998_244_353);
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, Default, PartialOrd, Ord, PartialEq, Eq)]
+struct NaiveDynModulus;
+#[cfg(test)]
+impl modular::Modulus for NaiveDynModulus {
+    fn modulus() -> i64 {
+        NAIVE_DYN_MODULUS.load(std::sync::atomic::Ordering::Relaxed) as i64
+    }
+}
+
+#[cfg(test)]
+fn bench_modint_mul<M: modular::Modulus>() -> modular::ModInt<M> {
+    let y = test::black_box(modular::ModInt::<M>::new(123_456_789));
+    let mut x = modular::ModInt::<M>::new(1);
+    for _ in 0..BENCH_N {
+        x = x * y;
+    }
+    x
+}
+
+#[test]
+fn test_naive_dyn_modulus() {
+    assert_eq!(bench_modint_mul::<NaiveDynModulus>().0, bench_modint_mul::<modular::ConstModulus<998_244_353>>().0);
+}
+
+#[bench]
+fn bench_modint_const(b: &mut test::Bencher) {
+    b.iter(|| bench_modint_mul::<modular::ConstModulus<998_244_353>>())
+}
+
+#[bench]
+fn bench_modint_dyn(b: &mut test::Bencher) {
+    modular::DynModulus::set(998_244_353);
+    b.iter(|| bench_modint_mul::<modular::DynModulus>())
+}
+
+#[bench]
+fn bench_modint_dyn_naive(b: &mut test::Bencher) {
+    b.iter(|| bench_modint_mul::<NaiveDynModulus>())
+}
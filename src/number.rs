@@ -133,6 +133,7 @@ pub fn modinv(a: i64, m: i64) -> i64 {
 
 #[snippet("ModComb")]
 #[snippet("modpow")]
+#[snippet("NTT")]
 #[allow(dead_code)]
 /// x ^ n % m
 pub fn modpow(x: i64, n: i64, m: i64) -> i64 {
@@ -159,6 +160,114 @@ fn test_modpow() {
     }
 }
 
+#[snippet("mod_sqrt")]
+#[doc = "x^2 = a (mod p) となるxを返す。pは素数。Tonelli-Shanks"]
+pub fn mod_sqrt(a: i64, p: i64) -> Option<i64> {
+    let a = a.rem_euclid(p);
+    if a == 0 || p == 2 {
+        return Some(a);
+    }
+    if modpow(a, (p-1)/2, p) != 1 {
+        return None;
+    }
+    // p-1 = q 2^s
+    let mut q = p-1;
+    let mut s = 0;
+    while q % 2 == 0 {
+        q /= 2;
+        s += 1;
+    }
+    let mut z = 2;
+    while modpow(z, (p-1)/2, p) != p-1 {
+        z += 1;
+    }
+    let mut m = s;
+    let mut c = modpow(z, q, p);
+    let mut t = modpow(a, q, p);
+    let mut r = modpow(a, (q+1)/2, p);
+    while t != 1 {
+        let mut i = 0;
+        let mut tt = t;
+        while tt != 1 {
+            tt = tt * tt % p;
+            i += 1;
+        }
+        let b = modpow(c, 1 << (m-i-1), p);
+        m = i;
+        c = b * b % p;
+        t = t * c % p;
+        r = r * b % p;
+    }
+    Some(r)
+}
+#[test]
+fn test_mod_sqrt() {
+    for &p in &[2, 3, 5, 13, 17, 97, 257] {
+        let mut residue = vec![false; p as usize];
+        for x in 0..p {
+            residue[(x * x % p) as usize] = true;
+        }
+        for a in 0..p {
+            match mod_sqrt(a, p) {
+                Some(x) => assert_eq!(x * x % p, a),
+                None => assert!(!residue[a as usize]),
+            }
+        }
+    }
+    let p = 998_244_353;
+    let x = mod_sqrt(7, p).unwrap();
+    assert_eq!(x * x % p, 7);
+}
+
+#[snippet("primitive_root")]
+#[snippet("NTT")]
+#[doc = "素数pの最小の原始根。p-1の素因数qすべてについてg^((p-1)/q) != 1"]
+pub fn primitive_root(p: i64) -> i64 {
+    if p == 2 {
+        return 1;
+    }
+    let mut factors = vec![];
+    let mut x = p - 1;
+    let mut d = 2;
+    while d * d <= x {
+        if x % d == 0 {
+            factors.push(d);
+            while x % d == 0 {
+                x /= d;
+            }
+        }
+        d += 1;
+    }
+    if x > 1 {
+        factors.push(x);
+    }
+    let mut g = 2;
+    loop {
+        if factors.iter().all(|&q| modpow(g, (p-1)/q, p) != 1) {
+            return g;
+        }
+        g += 1;
+    }
+}
+#[test]
+fn test_primitive_root() {
+    assert_eq!(primitive_root(2), 1);
+    assert_eq!(primitive_root(7), 3);
+    assert_eq!(primitive_root(998_244_353), 3);
+    assert_eq!(primitive_root(754_974_721), 11);
+    assert_eq!(primitive_root(2_013_265_921), 31);
+    for &p in &[3, 5, 11, 13, 97, 7681, 12289] {
+        let g = primitive_root(p);
+        let mut seen = vec![false; p as usize];
+        let mut x = 1;
+        for _ in 0..p-1 {
+            assert!(!seen[x as usize]);
+            seen[x as usize] = true;
+            x = x * g % p;
+        }
+    }
+}
+
 #[snippet("factorial")]
 fn factorial(a: i64, p: i64) -> i64 {
     if a == 0 {
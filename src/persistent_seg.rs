@@ -0,0 +1,239 @@
+use cargo_snippet::snippet;
+use crate::seg::Monoid;
+
+/// 永続セグ木
+///
+/// updateでは根から葉までの経路のノードだけを新しく作り(経路コピー)、
+/// それ以外のノードは前のバージョンと共有する。
+/// updateは新しいバージョン番号を返し、どのバージョンに対してもqueryできる。
+///
+/// ノード0は「すべてid」の部分木を表し、子も自分自身を指す。
+///
+/// 計算量:
+/// update: O(logN) 時間、O(logN) メモリ
+/// query: O(logN)
+
+#[snippet("PersistentSEG")]
+pub struct PersistentSEG<M: Monoid> {
+    pub n: usize,
+    val: Vec<M::T>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+    /// roots[v] = バージョンvの根
+    roots: Vec<usize>,
+}
+#[snippet("PersistentSEG")]
+impl<M: Monoid> PersistentSEG<M> {
+    /// バージョン0はすべてid
+    pub fn new(n: usize) -> PersistentSEG<M> {
+        PersistentSEG {
+            n: n,
+            val: vec![M::id()],
+            left: vec![0],
+            right: vec![0],
+            roots: vec![0],
+        }
+    }
+    /// バージョン0をvで初期化する。O(N)
+    pub fn from_vec(v: Vec<M::T>) -> PersistentSEG<M> {
+        let mut seg = Self::new(v.len());
+        let n = seg.n;
+        if n > 0 {
+            let root = seg.build(&v, 0, n);
+            seg.roots[0] = root;
+        }
+        seg
+    }
+    fn build(&mut self, v: &[M::T], l: usize, r: usize) -> usize {
+        if r - l == 1 {
+            return self.new_node(v[l].clone(), 0, 0);
+        }
+        let mid = (l+r)/2;
+        let a = self.build(v, l, mid);
+        let b = self.build(v, mid, r);
+        let x = M::op(&self.val[a], &self.val[b]);
+        self.new_node(x, a, b)
+    }
+    fn new_node(&mut self, x: M::T, l: usize, r: usize) -> usize {
+        self.val.push(x);
+        self.left.push(l);
+        self.right.push(r);
+        self.val.len() - 1
+    }
+    pub fn versions(&self) -> usize {
+        self.roots.len()
+    }
+    fn do_update(&mut self, node: usize, l: usize, r: usize, k: usize, x: M::T) -> usize {
+        if r - l == 1 {
+            return self.new_node(x, 0, 0);
+        }
+        let mid = (l+r)/2;
+        let (mut a, mut b) = (self.left[node], self.right[node]);
+        if k < mid {
+            a = self.do_update(a, l, mid, k, x);
+        } else {
+            b = self.do_update(b, mid, r, k, x);
+        }
+        let y = M::op(&self.val[a], &self.val[b]);
+        self.new_node(y, a, b)
+    }
+    /// バージョンversionでa[k]=xとしたものを新しいバージョンとして返す
+    pub fn update(&mut self, version: usize, k: usize, x: M::T) -> usize {
+        let n = self.n;
+        let root = self.do_update(self.roots[version], 0, n, k, x);
+        self.roots.push(root);
+        self.roots.len() - 1
+    }
+    fn do_query(&self, node: usize, l: usize, r: usize, a: usize, b: usize) -> M::T {
+        if node == 0 || r <= a || b <= l {
+            return M::id();
+        }
+        if a <= l && r <= b {
+            return self.val[node].clone();
+        }
+        let mid = (l+r)/2;
+        let vl = self.do_query(self.left[node], l, mid, a, b);
+        let vr = self.do_query(self.right[node], mid, r, a, b);
+        M::op(&vl, &vr)
+    }
+    /// バージョンversionでの[a,b)
+    pub fn query(&self, version: usize, a: usize, b: usize) -> M::T {
+        self.do_query(self.roots[version], 0, self.n, a, b)
+    }
+    pub fn get(&self, version: usize, k: usize) -> M::T {
+        self.query(version, k, k+1)
+    }
+}
+
+/// 区間[l,r)でk番目(0-indexed)に小さい値
+///
+/// xsを先頭から一つずつ数え上げた永続セグ木を作っておくと、
+/// バージョンrとバージョンlの差が区間[l,r)の値の個数になる。
+/// 二つの根から同時に降りていけばO(logN)で求まる。
+
+#[snippet("RangeKth")]
+struct COUNT;
+#[snippet("RangeKth")]
+impl Monoid for COUNT {
+    type T = usize;
+    fn id() -> Self::T {
+        0
+    }
+    fn op(a: &Self::T, b: &Self::T) -> Self::T {
+        *a + *b
+    }
+}
+#[snippet("RangeKth")]
+pub struct RangeKth {
+    seg: PersistentSEG<COUNT>,
+    /// 座標圧縮した値
+    vals: Vec<i64>,
+}
+#[snippet("RangeKth")]
+impl RangeKth {
+    pub fn new(xs: &[i64]) -> RangeKth {
+        let mut vals = xs.to_vec();
+        vals.sort();
+        vals.dedup();
+        let mut seg = PersistentSEG::new(vals.len());
+        let mut v = 0;
+        for x in xs {
+            let i = vals.binary_search(x).unwrap();
+            let c = seg.get(v, i);
+            v = seg.update(v, i, c + 1);
+        }
+        RangeKth {
+            seg: seg,
+            vals: vals,
+        }
+    }
+    /// [l,r)でk番目(0-indexed)に小さい値。k < r-l
+    pub fn kth_smallest(&self, l: usize, r: usize, k: usize) -> i64 {
+        assert!(k < r - l);
+        let seg = &self.seg;
+        let mut a = seg.roots[l];
+        let mut b = seg.roots[r];
+        let mut k = k;
+        let mut lo = 0;
+        let mut hi = seg.n;
+        while hi - lo > 1 {
+            let mid = (lo+hi)/2;
+            let c = seg.val[seg.left[b]] - seg.val[seg.left[a]];
+            if k < c {
+                a = seg.left[a];
+                b = seg.left[b];
+                hi = mid;
+            } else {
+                k -= c;
+                a = seg.right[a];
+                b = seg.right[b];
+                lo = mid;
+            }
+        }
+        self.vals[lo]
+    }
+}
+
+#[cfg(test)]
+use crate::seg::SUM;
+
+#[test]
+fn test_persistent_seg_simple() {
+    let mut seg: PersistentSEG<SUM> = PersistentSEG::from_vec(vec![1, 2, 3, 4]);
+    let v1 = seg.update(0, 1, 10);
+    let v2 = seg.update(v1, 3, 0);
+    let v3 = seg.update(0, 0, 5);
+    assert_eq!(seg.query(0, 0, 4), 10);
+    assert_eq!(seg.query(v1, 0, 4), 18);
+    assert_eq!(seg.query(v2, 0, 4), 14);
+    assert_eq!(seg.query(v3, 0, 4), 14);
+    assert_eq!(seg.get(v2, 1), 10);
+    assert_eq!(seg.get(v3, 1), 2);
+    assert_eq!(seg.versions(), 4);
+}
+
+#[test]
+fn test_persistent_seg_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for &n in &[1, 2, 7, 16, 33] {
+        let mut seg: PersistentSEG<SUM> = PersistentSEG::new(n);
+        let mut history = vec![vec![0; n]];
+        for _ in 0..300 {
+            let v = rand.rand(history.len() as u64) as usize;
+            if rand.rand(2) == 0 {
+                let k = rand.rand(n as u64) as usize;
+                let x = rand.rand(100) as i64;
+                let mut a = history[v].clone();
+                a[k] = x;
+                history.push(a);
+                assert_eq!(seg.update(v, k, x), history.len() - 1);
+            } else {
+                let l = rand.rand(n as u64 + 1) as usize;
+                let r = l + rand.rand((n - l) as u64 + 1) as usize;
+                assert_eq!(seg.query(v, l, r), history[v][l..r].iter().sum::<i64>());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_range_kth_vs_wavelet_matrix() {
+    use crate::wavelet_matrix::WM;
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    let n = 500;
+    let xs: Vec<i64> = (0..n).map(|_| rand.rand(200) as i64).collect();
+    let kth = RangeKth::new(&xs);
+    let wm = WM::new(xs.iter().map(|&x| x as u64).collect());
+    for _ in 0..1000 {
+        let l = rand.rand(n as u64) as usize;
+        let r = l + 1 + rand.rand((n - l) as u64) as usize;
+        let k = rand.rand((r - l) as u64) as usize;
+        let mut v = xs[l..r].to_vec();
+        v.sort();
+        assert_eq!(kth.kth_smallest(l, r, k), v[k]);
+        // quantileは1-indexedでk番目に大きい値
+        assert_eq!(kth.kth_smallest(l, r, k), wm.quantile(l, r, r - l - k) as i64);
+    }
+}
@@ -44,4 +44,42 @@ fn bench_ntt_fft(b: &mut test::Bencher) {
     b.iter(||
         fft::multiply(&x, &x)
     )
-}
\ No newline at end of file
+}
+
+#[bench]
+fn bench_ntt_convolve(b: &mut test::Bencher) {
+    let mut x = vec![0;N];
+    for i in 0..N {
+        x[i] = i as i64;
+    }
+    let ntt = ntt::NTT::new(998_244_353);
+    b.iter(||
+        ntt.convolve(&x, &x)
+    )
+}
+
+#[bench]
+fn bench_ntt_convolve_barrett(b: &mut test::Bencher) {
+    use crate::reduction::Barrett;
+    let mut x = vec![0;N];
+    for i in 0..N {
+        x[i] = i as i64;
+    }
+    let ntt = ntt::NTT::with_reduction(Barrett::new(998_244_353));
+    b.iter(||
+        ntt.convolve(&x, &x)
+    )
+}
+
+#[bench]
+fn bench_ntt_convolve_prepared(b: &mut test::Bencher) {
+    let mut x = vec![0;N];
+    for i in 0..N {
+        x[i] = i as i64;
+    }
+    let ntt = ntt::NTT::new(998_244_353);
+    let fx = ntt.prepare(&x, N);
+    b.iter(||
+        ntt.convolve_prepared(&fx, &x)
+    )
+}
@@ -0,0 +1,515 @@
+use cargo_snippet::snippet;
+use super::ntt::NTT;
+use crate::number::{modinv, modpow, mod_sqrt};
+use crate::modint::modular::{ModInt, Modulus};
+
+/// 形式的冪級数
+///
+/// 係数はmod moで持ち、掛け算はNTT::convolveで行う。
+/// moはNTTが使える c*2^k+1 の形の素数(< 2^31)であること。
+/// 原始根はNTTが自動で求め、回転因子の表は法ごとにキャッシュされるので
+/// 掛け算のたびに作り直すことはない。
+/// ModInt<M>の列とはfrom_modint/to_modintで相互に変換できる。
+///
+/// nを引数に取る演算はmod x^nで計算する。
+///
+/// 計算量:
+/// inv, log, exp, sqrt, pow: O(N logN)
+/// div_rem: O(N logN)
+/// multipoint_eval, interpolate: O(N log^2N)
+/// taylor_shift: O(N logN)
+
+#[snippet("FormalPowerSeries")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormalPowerSeries {
+    pub coeff: Vec<i64>,
+    pub mo: i64,
+}
+#[snippet("FormalPowerSeries")]
+impl FormalPowerSeries {
+    pub fn new(coeff: Vec<i64>, mo: i64) -> Self {
+        FormalPowerSeries {
+            coeff: coeff.into_iter().map(|x| x.rem_euclid(mo)).collect(),
+            mo: mo,
+        }
+    }
+    pub fn from_modint<M: Modulus>(coeff: &[ModInt<M>]) -> Self {
+        FormalPowerSeries {
+            coeff: coeff.iter().map(|x| x.0).collect(),
+            mo: M::modulus(),
+        }
+    }
+    pub fn to_modint<M: Modulus>(&self) -> Vec<ModInt<M>> {
+        assert!(M::modulus() == self.mo);
+        self.coeff.iter().map(|&x| ModInt::new(x)).collect()
+    }
+    fn with(&self, coeff: Vec<i64>) -> Self {
+        FormalPowerSeries {
+            coeff: coeff,
+            mo: self.mo,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.coeff.len()
+    }
+    pub fn at(&self, i: usize) -> i64 {
+        if i < self.len() { self.coeff[i] } else { 0 }
+    }
+    /// mod x^n (足りない分は0で埋める)
+    pub fn prefix(&self, n: usize) -> Self {
+        self.with((0..n).map(|i| self.at(i)).collect())
+    }
+    /// 末尾の0を取り除く
+    pub fn trim(mut self) -> Self {
+        while self.coeff.last() == Some(&0) {
+            self.coeff.pop();
+        }
+        self
+    }
+    pub fn add(&self, other: &Self) -> Self {
+        let n = std::cmp::max(self.len(), other.len());
+        self.with((0..n).map(|i| (self.at(i) + other.at(i)) % self.mo).collect())
+    }
+    pub fn sub(&self, other: &Self) -> Self {
+        let n = std::cmp::max(self.len(), other.len());
+        self.with((0..n).map(|i| (self.at(i) + self.mo - other.at(i)) % self.mo).collect())
+    }
+    pub fn scale(&self, c: i64) -> Self {
+        let c = c.rem_euclid(self.mo);
+        self.with(self.coeff.iter().map(|&x| x * c % self.mo).collect())
+    }
+    pub fn multiply(&self, other: &Self) -> Self {
+        assert!(self.mo == other.mo);
+        let n = self.len();
+        let m = other.len();
+        if n == 0 || m == 0 {
+            return self.with(vec![]);
+        }
+        if std::cmp::min(n, m) <= 32 {
+            let mut c = vec![0; n+m-1];
+            for i in 0..n {
+                for j in 0..m {
+                    c[i+j] = (c[i+j] + self.coeff[i] * other.coeff[j]) % self.mo;
+                }
+            }
+            return self.with(c);
+        }
+        let mut c = NTT::new(self.mo).convolve(&self.coeff, &other.coeff);
+        c.truncate(n+m-1);
+        self.with(c)
+    }
+    pub fn diff(&self) -> Self {
+        self.with((1..self.len()).map(|i| self.coeff[i] * i as i64 % self.mo).collect())
+    }
+    pub fn integral(&self) -> Self {
+        let n = self.len();
+        let mut inv = vec![1; n+1];
+        for i in 2..n+1 {
+            inv[i] = (self.mo - self.mo / i as i64) * inv[self.mo as usize % i] % self.mo;
+        }
+        let mut c = vec![0; n+1];
+        for i in 0..n {
+            c[i+1] = self.coeff[i] * inv[i+1] % self.mo;
+        }
+        self.with(c)
+    }
+    /// f g = 1 (mod x^n)
+    pub fn inv(&self, n: usize) -> Self {
+        assert!(self.at(0) != 0);
+        let mut g = self.with(vec![modinv(self.at(0), self.mo)]);
+        let mut k = 1;
+        while k < n {
+            k *= 2;
+            // g <- g (2 - fg)
+            let h = self.with(vec![2]).sub(&self.prefix(k).multiply(&g).prefix(k));
+            g = g.multiply(&h).prefix(k);
+        }
+        g.prefix(n)
+    }
+    /// f(0) = 1
+    pub fn log(&self, n: usize) -> Self {
+        assert!(self.at(0) == 1);
+        if n == 0 {
+            return self.with(vec![]);
+        }
+        self.prefix(n).diff().multiply(&self.inv(n)).prefix(n-1).integral()
+    }
+    /// f(0) = 0
+    pub fn exp(&self, n: usize) -> Self {
+        assert!(self.at(0) == 0);
+        let mut g = self.with(vec![1]);
+        let mut k = 1;
+        while k < n {
+            k *= 2;
+            // g <- g (1 - log g + f)
+            let h = self.prefix(k).sub(&g.log(k)).add(&self.with(vec![1]));
+            g = g.multiply(&h).prefix(k);
+        }
+        g.prefix(n)
+    }
+    /// g^2 = f (mod x^n) となるgの一つ。存在しなければNone
+    pub fn sqrt(&self, n: usize) -> Option<Self> {
+        let i = match self.coeff.iter().position(|&x| x != 0) {
+            Some(i) => i,
+            None => return Some(self.with(vec![0; n])),
+        };
+        // f = 0 (mod x^n) なら0が答え。奇数次やsqrtが無い場合より先に見る
+        if i >= n {
+            return Some(self.with(vec![0; n]));
+        }
+        if i % 2 == 1 {
+            return None;
+        }
+        let s = mod_sqrt(self.coeff[i], self.mo)?;
+        let f = self.with(self.coeff[i..].to_vec());
+        let m = n - i / 2;
+        let inv2 = modinv(2, self.mo);
+        let mut g = self.with(vec![s]);
+        let mut k = 1;
+        while k < m {
+            k *= 2;
+            // g <- (g + f/g) / 2
+            g = g.add(&f.prefix(k).multiply(&g.inv(k)).prefix(k)).scale(inv2);
+        }
+        let mut c = vec![0; i / 2];
+        c.extend(g.prefix(m).coeff);
+        Some(self.with(c))
+    }
+    /// f^k (mod x^n)
+    pub fn pow(&self, k: u64, n: usize) -> Self {
+        if k == 0 {
+            return self.with(vec![1]).prefix(n);
+        }
+        let i = match self.coeff.iter().position(|&x| x != 0) {
+            Some(i) => i,
+            None => return self.with(vec![0; n]),
+        };
+        if i as u128 * k as u128 >= n as u128 {
+            return self.with(vec![0; n]);
+        }
+        let shift = i * k as usize;
+        let m = n - shift;
+        let c = self.coeff[i];
+        let f = self.with(self.coeff[i..].to_vec()).scale(modinv(c, self.mo));
+        let g = f.log(m).scale((k % self.mo as u64) as i64).exp(m);
+        let g = g.scale(modpow(c, (k % (self.mo - 1) as u64) as i64, self.mo));
+        let mut res = vec![0; shift];
+        res.extend(g.coeff);
+        self.with(res)
+    }
+    /// f = g q + r (deg r < deg g)
+    pub fn div_rem(&self, g: &Self) -> (Self, Self) {
+        let f = self.clone().trim();
+        let g = g.clone().trim();
+        assert!(g.len() > 0);
+        if f.len() < g.len() {
+            return (self.with(vec![]), f);
+        }
+        let d = f.len() - g.len() + 1;
+        let mut rf = f.coeff.clone();
+        rf.reverse();
+        let mut rg = g.coeff.clone();
+        rg.reverse();
+        let rf = self.with(rf).prefix(d);
+        let rg = self.with(rg).prefix(d);
+        let mut q = rf.multiply(&rg.inv(d)).prefix(d).coeff;
+        q.reverse();
+        let q = self.with(q);
+        let r = f.sub(&g.multiply(&q)).prefix(g.len() - 1);
+        (q.trim(), r.trim())
+    }
+    pub fn eval(&self, x: i64) -> i64 {
+        let x = x.rem_euclid(self.mo);
+        let mut res = 0;
+        for &c in self.coeff.iter().rev() {
+            res = (res * x + c) % self.mo;
+        }
+        res
+    }
+    /// tree[k] = prod (x - xs[i]) (iはノードkの担当範囲)
+    fn subproduct_tree(xs: &[i64], mo: i64) -> (usize, Vec<Self>) {
+        let sz = xs.len().next_power_of_two();
+        let mut tree = vec![FormalPowerSeries::new(vec![1], mo); 2 * sz];
+        for i in 0..xs.len() {
+            tree[sz + i] = FormalPowerSeries::new(vec![-xs[i], 1], mo);
+        }
+        for k in (1..sz).rev() {
+            tree[k] = tree[2*k].multiply(&tree[2*k+1]);
+        }
+        (sz, tree)
+    }
+    pub fn multipoint_eval(&self, xs: &[i64]) -> Vec<i64> {
+        if xs.is_empty() {
+            return vec![];
+        }
+        let (sz, tree) = Self::subproduct_tree(xs, self.mo);
+        let mut rem = vec![self.with(vec![]); 2 * sz];
+        rem[1] = self.div_rem(&tree[1]).1;
+        for k in 2..sz + xs.len() {
+            rem[k] = rem[k/2].div_rem(&tree[k]).1;
+        }
+        (0..xs.len()).map(|i| rem[sz + i].at(0)).collect()
+    }
+    /// f(xs[i]) = ys[i] となる次数 < len(xs) のf。xsは相異なること
+    pub fn interpolate(xs: &[i64], ys: &[i64], mo: i64) -> Self {
+        assert!(xs.len() == ys.len());
+        if xs.is_empty() {
+            return FormalPowerSeries::new(vec![], mo);
+        }
+        let (sz, tree) = Self::subproduct_tree(xs, mo);
+        let d = tree[1].diff().multipoint_eval(xs);
+        let mut res = vec![FormalPowerSeries::new(vec![], mo); 2 * sz];
+        for i in 0..xs.len() {
+            res[sz + i] = FormalPowerSeries::new(vec![ys[i].rem_euclid(mo) * modinv(d[i], mo) % mo], mo);
+        }
+        for k in (1..sz).rev() {
+            res[k] = res[2*k].multiply(&tree[2*k+1]).add(&res[2*k+1].multiply(&tree[2*k]));
+        }
+        res[1].clone().trim()
+    }
+    /// f(x + c)
+    pub fn taylor_shift(&self, c: i64) -> Self {
+        let n = self.len();
+        let mo = self.mo;
+        let mut fact = vec![1; n+1];
+        for i in 1..n+1 {
+            fact[i] = fact[i-1] * i as i64 % mo;
+        }
+        let mut fact_inv = vec![1; n+1];
+        fact_inv[n] = modinv(fact[n], mo);
+        for i in (0..n).rev() {
+            fact_inv[i] = fact_inv[i+1] * (i+1) as i64 % mo;
+        }
+        let c = c.rem_euclid(mo);
+        // a[n-1-j] = f_j j!, b[k] = c^k / k!
+        let a = self.with((0..n).map(|j| self.coeff[n-1-j] * fact[n-1-j] % mo).collect());
+        let mut b = vec![0; n];
+        let mut p = 1;
+        for k in 0..n {
+            b[k] = p * fact_inv[k] % mo;
+            p = p * c % mo;
+        }
+        let ab = a.multiply(&self.with(b));
+        self.with((0..n).map(|i| ab.at(n-1-i) * fact_inv[i] % mo).collect())
+    }
+}
+
+#[cfg(test)]
+const MO: i64 = 998_244_353;
+
+#[cfg(test)]
+fn random_fps(rand: &mut crate::xorshift::Xorshift, n: usize) -> FormalPowerSeries {
+    FormalPowerSeries::new((0..n).map(|_| rand.rand(MO as u64) as i64).collect(), MO)
+}
+
+#[cfg(test)]
+fn naive_multiply(a: &[i64], b: &[i64]) -> Vec<i64> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut c = vec![0; a.len()+b.len()-1];
+    for i in 0..a.len() {
+        for j in 0..b.len() {
+            c[i+j] = (c[i+j] + a[i] * b[j]) % MO;
+        }
+    }
+    c
+}
+
+#[cfg(test)]
+fn naive_inv(f: &[i64], n: usize) -> Vec<i64> {
+    let f0_inv = modinv(f[0], MO);
+    let mut g = vec![0; n];
+    g[0] = f0_inv;
+    for i in 1..n {
+        let mut s = 0;
+        for j in 1..std::cmp::min(i, f.len()-1)+1 {
+            s = (s + f[j] * g[i-j]) % MO;
+        }
+        g[i] = (MO - s) * f0_inv % MO;
+    }
+    g
+}
+
+#[test]
+fn test_fps_multiply() {
+    let mut rand = crate::xorshift::Xorshift::new();
+    for &(n, m) in &[(1, 1), (3, 100), (100, 200), (513, 300)] {
+        let a = random_fps(&mut rand, n);
+        let b = random_fps(&mut rand, m);
+        assert_eq!(a.multiply(&b).coeff, naive_multiply(&a.coeff, &b.coeff));
+    }
+}
+
+#[test]
+fn test_fps_other_modulus() {
+    use crate::modint::modular::ModP;
+    // 原始根が3でない法 (原始根は11)
+    const P: u32 = 754_974_721;
+    let mut rand = crate::xorshift::Xorshift::new();
+    let a: Vec<ModP<P>> = (0..100).map(|_| ModP::new(rand.rand(P as u64) as i64)).collect();
+    let f = FormalPowerSeries::from_modint(&a);
+    assert_eq!(f.mo, P as i64);
+    let g = f.inv(100);
+    let one: Vec<ModP<P>> = f.multiply(&g).prefix(100).to_modint();
+    assert_eq!(one[0], 1.into());
+    assert!(one[1..].iter().all(|x| x.0 == 0));
+}
+
+#[test]
+fn test_fps_inv() {
+    let mut rand = crate::xorshift::Xorshift::new();
+    for &n in &[1, 2, 7, 64, 300] {
+        let mut f = random_fps(&mut rand, n);
+        f.coeff[0] = 1 + rand.rand(MO as u64 - 1) as i64;
+        assert_eq!(f.inv(n).coeff, naive_inv(&f.coeff, n));
+    }
+}
+
+#[test]
+fn test_fps_log_exp() {
+    let mut rand = crate::xorshift::Xorshift::new();
+    for &n in &[1, 2, 10, 100, 300] {
+        // exp: n g_n = sum k f_k g_{n-k}
+        let mut f = random_fps(&mut rand, n);
+        f.coeff[0] = 0;
+        let mut g = vec![0; n];
+        g[0] = 1;
+        for i in 1..n {
+            let mut s = 0;
+            for k in 1..i+1 {
+                s = (s + k as i64 * f.coeff[k] % MO * g[i-k]) % MO;
+            }
+            g[i] = s * modinv(i as i64, MO) % MO;
+        }
+        assert_eq!(f.exp(n).coeff, g);
+
+        // log: integral(f' / f)
+        let g = FormalPowerSeries::new(g, MO);
+        let d = naive_multiply(&g.diff().coeff, &naive_inv(&g.coeff, n));
+        let expected = FormalPowerSeries::new(d, MO).prefix(n-1).integral();
+        assert_eq!(g.log(n), expected);
+        assert_eq!(g.log(n), f);
+    }
+}
+
+#[test]
+fn test_fps_sqrt() {
+    let mut rand = crate::xorshift::Xorshift::new();
+    for &n in &[1, 2, 10, 100, 300] {
+        for &zeros in &[0, 2, 5] {
+            let mut c = vec![0; zeros];
+            c.extend(random_fps(&mut rand, n).coeff);
+            let f = FormalPowerSeries::new(c, MO);
+            match f.sqrt(n) {
+                Some(g) => {
+                    assert_eq!(g.len(), n);
+                    assert_eq!(g.multiply(&g).prefix(n), f.prefix(n));
+                },
+                None => {
+                    let i = f.coeff.iter().position(|&x| x != 0).unwrap();
+                    assert!(i % 2 == 1 || mod_sqrt(f.coeff[i], MO).is_none());
+                },
+            }
+        }
+    }
+    let f = FormalPowerSeries::new(vec![0, 0, 4, 4, 1], MO);
+    assert_eq!(f.sqrt(4).unwrap().coeff, vec![0, MO-2, MO-1, 0]);
+    assert_eq!(FormalPowerSeries::new(vec![0, 1], MO).sqrt(3), None);
+    let f = FormalPowerSeries::new(vec![0, 0, 0, 5], MO);
+    assert_eq!(f.sqrt(2).unwrap().coeff, vec![0, 0]);
+    assert_eq!(f.sqrt(3).unwrap().coeff, vec![0, 0, 0]);
+    assert_eq!(f.sqrt(4), None);
+    // 5は平方非剰余だが、mod x^3 では0
+    let f = FormalPowerSeries::new(vec![0, 0, 5], MO);
+    assert_eq!(mod_sqrt(5, MO), None);
+    assert_eq!(f.sqrt(2).unwrap().coeff, vec![0, 0]);
+    assert_eq!(f.sqrt(3), None);
+}
+
+#[test]
+fn test_fps_pow() {
+    fn naive_pow(f: &FormalPowerSeries, k: u64, n: usize) -> FormalPowerSeries {
+        let mut res = FormalPowerSeries::new(vec![1], MO).prefix(n);
+        let mut x = f.prefix(n);
+        let mut k = k;
+        while k > 0 {
+            if k & 1 == 1 {
+                res = FormalPowerSeries::new(naive_multiply(&res.coeff, &x.coeff), MO).prefix(n);
+            }
+            x = FormalPowerSeries::new(naive_multiply(&x.coeff, &x.coeff), MO).prefix(n);
+            k >>= 1;
+        }
+        res
+    }
+    let mut rand = crate::xorshift::Xorshift::new();
+    for &n in &[1, 5, 50, 200] {
+        for &zeros in &[0, 1, 3] {
+            for &k in &[0, 1, 2, 7, 1_000_000_000_000_000_000] {
+                let mut c = vec![0; zeros];
+                c.extend(random_fps(&mut rand, n).coeff);
+                let f = FormalPowerSeries::new(c, MO);
+                assert_eq!(f.pow(k, n), naive_pow(&f, k, n));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fps_div_rem() {
+    let mut rand = crate::xorshift::Xorshift::new();
+    for &(n, m) in &[(1, 1), (5, 10), (10, 5), (300, 120), (200, 1)] {
+        let f = random_fps(&mut rand, n);
+        let mut g = random_fps(&mut rand, m);
+        g.coeff[m-1] = 1 + rand.rand(MO as u64 - 1) as i64;
+        // 筆算
+        let mut r = f.coeff.clone();
+        let mut q = vec![0; if n >= m { n-m+1 } else { 0 }];
+        let lead_inv = modinv(g.coeff[m-1], MO);
+        for i in (0..q.len()).rev() {
+            let c = r[i+m-1] * lead_inv % MO;
+            q[i] = c;
+            for j in 0..m {
+                r[i+j] = (r[i+j] + MO - c * g.coeff[j] % MO) % MO;
+            }
+        }
+        let (q2, r2) = f.div_rem(&g);
+        assert_eq!(q2, FormalPowerSeries::new(q, MO).trim());
+        assert_eq!(r2, FormalPowerSeries::new(r, MO).trim());
+    }
+}
+
+#[test]
+fn test_fps_multipoint_eval_interpolate() {
+    let mut rand = crate::xorshift::Xorshift::new();
+    for &(n, m) in &[(1, 1), (10, 3), (3, 10), (100, 130)] {
+        let f = random_fps(&mut rand, n);
+        let xs: Vec<i64> = (0..m).map(|_| rand.rand(MO as u64) as i64).collect();
+        let expected: Vec<i64> = xs.iter().map(|&x| f.eval(x)).collect();
+        assert_eq!(f.multipoint_eval(&xs), expected);
+    }
+    for &m in &[1, 2, 9, 100] {
+        let xs: Vec<i64> = (0..m).map(|i| i * 3 + 1).collect();
+        let f = random_fps(&mut rand, m as usize).trim();
+        let ys: Vec<i64> = xs.iter().map(|&x| f.eval(x)).collect();
+        assert_eq!(FormalPowerSeries::interpolate(&xs, &ys, MO), f);
+    }
+}
+
+#[test]
+fn test_fps_taylor_shift() {
+    let mut rand = crate::xorshift::Xorshift::new();
+    for &n in &[1, 2, 10, 100] {
+        let f = random_fps(&mut rand, n);
+        let c = rand.rand(MO as u64) as i64;
+        // sum f_j (x+c)^j
+        let mut expected = FormalPowerSeries::new(vec![0; n], MO);
+        let mut p = FormalPowerSeries::new(vec![1], MO);
+        for j in 0..n {
+            expected = expected.add(&p.scale(f.coeff[j]));
+            p = FormalPowerSeries::new(naive_multiply(&p.coeff, &[c, 1]), MO);
+        }
+        assert_eq!(f.taylor_shift(c), expected);
+        assert_eq!(f.taylor_shift(c).eval(5), f.eval(5 + c));
+    }
+}
@@ -2,4 +2,5 @@ mod fft;
 mod karatsuba;
 mod ntt;
 mod polynomial;
+mod fps;
 mod bench;
\ No newline at end of file
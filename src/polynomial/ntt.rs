@@ -1,9 +1,20 @@
 use cargo_snippet::snippet;
-use crate::number::{modinv, modpow};
+use crate::number::{modinv, primitive_root};
 use crate::garner::garner;
+use crate::modint::modular::{ModInt, Modulus};
+use crate::reduction::{Reduction, Montgomery, Barrett};
 
 /// mod pの下でFTTのようなことをする。
 /// 計算量: O(N logN)
+///
+/// pはc*2^k+1の形の素数(< 2^31)で、原始根は自動で求める。
+/// 原始根と回転因子の表は法ごとにスレッドローカルにキャッシュするので、
+/// NTTを何度作り直しても計算は一度だけで済む。
+/// 値はReduction(既定はMontgomery)の内部表現のu32で持ち、
+/// その場でバタフライ演算を行う。
+///
+/// 前向きの変換はビット反転順で出力し、逆変換はビット反転順の入力を受け取るので
+/// 並べ替えは不要。(AtCoder Libraryと同じ方式)
 
 #[test]
 fn test_ntt() {
@@ -14,100 +25,232 @@ fn test_ntt() {
 }
 
 #[snippet("NTT")]
-struct NTT {
+pub struct NTT<R: Reduction = Montgomery> {
     pub mo: i64,
+    red: R,
+    /// 長さ2^max_logまで変換できる
+    pub max_log: usize,
+    rate2: Vec<u32>,
+    irate2: Vec<u32>,
 }
 #[snippet("NTT")]
-impl NTT {
-    pub fn new(mo: i64) -> NTT {
+impl NTT<Montgomery> {
+    pub fn new(mo: i64) -> NTT<Montgomery> {
+        NTT::with_reduction(Montgomery::new(mo as u32))
+    }
+}
+#[snippet("NTT")]
+/// 法ごとの回転因子の表(普通の値で持つ)
+struct NTTTable {
+    rate2: Vec<u32>,
+    irate2: Vec<u32>,
+}
+#[snippet("NTT")]
+thread_local! {
+    /// 原始根と回転因子の計算を法ごとに一度だけで済ませる
+    static NTT_TABLES: std::cell::RefCell<std::collections::HashMap<u32, std::rc::Rc<NTTTable>>> = Default::default();
+}
+#[snippet("NTT")]
+fn ntt_table(mo: u32) -> std::rc::Rc<NTTTable> {
+    NTT_TABLES.with(|tables| {
+        tables.borrow_mut().entry(mo).or_insert_with(|| {
+            let red = Barrett::new(mo);
+            let g = primitive_root(mo as i64) as u32;
+            let rank2 = (mo - 1).trailing_zeros() as usize;
+            let mut root = vec![0; rank2 + 1];
+            let mut iroot = vec![0; rank2 + 1];
+            root[rank2] = red.pow(g, ((mo - 1) >> rank2) as u64);
+            iroot[rank2] = red.pow(root[rank2], (mo - 2) as u64);
+            for i in (0..rank2).rev() {
+                root[i] = red.mul(root[i+1], root[i+1]);
+                iroot[i] = red.mul(iroot[i+1], iroot[i+1]);
+            }
+            let mut rate2 = vec![];
+            let mut irate2 = vec![];
+            let mut prod = 1;
+            let mut iprod = 1;
+            for i in 0..rank2.saturating_sub(1) {
+                rate2.push(red.mul(root[i+2], prod));
+                irate2.push(red.mul(iroot[i+2], iprod));
+                prod = red.mul(prod, iroot[i+2]);
+                iprod = red.mul(iprod, root[i+2]);
+            }
+            std::rc::Rc::new(NTTTable { rate2: rate2, irate2: irate2 })
+        }).clone()
+    })
+}
+#[snippet("NTT")]
+impl<R: Reduction> NTT<R> {
+    /// 回転因子の表は法ごとにキャッシュされるので、何度作ってもよい
+    pub fn with_reduction(red: R) -> NTT<R> {
+        let mo = red.modulus();
+        let table = ntt_table(mo);
         NTT {
-            mo: mo,
+            mo: mo as i64,
+            max_log: (mo - 1).trailing_zeros() as usize,
+            rate2: table.rate2.iter().map(|&x| red.to_repr(x)).collect(),
+            irate2: table.irate2.iter().map(|&x| red.to_repr(x)).collect(),
+            red: red,
         }
     }
-    fn _ntt(&self, a: &mut [i64], n: usize, inverse: bool) {
-        let g = 3;
-        let mut h = modpow(g, (self.mo-1)/n as i64, self.mo);
-        if inverse {
-            h = modinv(h, self.mo);
-        }
-
-        let mut i = 0;
-        for j in 1..n-1 {
-            let mut k = n>>1;
-            loop {
-                i ^= k;
-                if k > i {
-                    k >>= 1;
-                } else {
-                    break;
+    fn butterfly(&self, a: &mut [u32]) {
+        let red = &self.red;
+        let h = a.len().trailing_zeros() as usize;
+        for ph in 1..h+1 {
+            let w = 1 << (ph-1);
+            let p = 1 << (h-ph);
+            let mut now = red.to_repr(1);
+            for s in 0..w {
+                let offset = s << (h-ph+1);
+                for i in offset..offset+p {
+                    let l = a[i];
+                    let r = red.mul(a[i+p], now);
+                    a[i] = red.add(l, r);
+                    a[i+p] = red.sub(l, r);
+                }
+                if s + 1 < w {
+                    now = red.mul(now, self.rate2[(!s).trailing_zeros() as usize]);
                 }
-            }
-            if j < i {
-                let tmp = a[i];
-                a[i] = a[j];
-                a[j] = tmp;
             }
         }
-
-        let mut m = 1;
-        while m < n {
-            let m2 = m * 2;
-            let base = modpow(h, (n/m2) as i64, self.mo);
-            let mut w = 1;
-            for x in 0..m {
-                let mut s = x;
-                while s < n {
-                    let u = a[s];
-                    let d = (a[s+m] * w) % self.mo;
-                    a[s] = u + d;
-                    if a[s] >= self.mo { a[s] -= self.mo; }
-                    a[s+m] = u - d;
-                    if a[s+m] < 0 { a[s+m] += self.mo; }
-                    s += m2;
+    }
+    fn butterfly_inv(&self, a: &mut [u32]) {
+        let red = &self.red;
+        let h = a.len().trailing_zeros() as usize;
+        for ph in (1..h+1).rev() {
+            let w = 1 << (ph-1);
+            let p = 1 << (h-ph);
+            let mut inow = red.to_repr(1);
+            for s in 0..w {
+                let offset = s << (h-ph+1);
+                for i in offset..offset+p {
+                    let l = a[i];
+                    let r = a[i+p];
+                    a[i] = red.add(l, r);
+                    a[i+p] = red.mul(red.sub(l, r), inow);
+                }
+                if s + 1 < w {
+                    inow = red.mul(inow, self.irate2[(!s).trailing_zeros() as usize]);
                 }
-                w = (w * base) % self.mo;
             }
-            m *= 2;
         }
-        for i in 0..n {
-            if a[i] < 0 {
-                a[i] += self.mo;
-            }
+    }
+    /// aを長さnに0埋めして変換する。nは2の累乗
+    fn transform(&self, a: &[i64], n: usize) -> Vec<u32> {
+        assert!(n.is_power_of_two() && n.trailing_zeros() as usize <= self.max_log);
+        let mut fa = vec![0; n];
+        for i in 0..a.len() {
+            fa[i] = self.red.to_repr(a[i].rem_euclid(self.mo) as u32);
         }
+        self.butterfly(&mut fa);
+        fa
     }
-    fn ntt(&self, a: &mut [i64], n: usize) {
-        self._ntt(a, n, false);
+    /// 変換後のまま各点で掛けて逆変換し、先頭len個を返す
+    fn pointwise_inverse(&self, fa: &[u32], fb: &[u32], len: usize) -> Vec<i64> {
+        let n = fa.len();
+        let mut c: Vec<u32> = (0..n).map(|i| self.red.mul(fa[i], fb[i])).collect();
+        self.butterfly_inv(&mut c);
+        let n_inv = self.red.pow(self.red.to_repr(n as u32), (self.mo - 2) as u64);
+        c.truncate(len);
+        c.into_iter().map(|x| self.red.from_repr(self.red.mul(x, n_inv)) as i64).collect()
     }
-    fn intt(&self, a: &mut [i64], n: usize) {
-        self._ntt(a, n, true);
-        let n_inv = modinv(a.len() as i64, self.mo);
-        for i in 0..n {
-            a[i] = (a[i] * n_inv) % self.mo;
+    /// 長さはa.len()+b.len()-1
+    pub fn convolve(&self, a: &[i64], b: &[i64]) -> Vec<i64> {
+        if a.is_empty() || b.is_empty() {
+            return vec![];
         }
+        let len = a.len() + b.len() - 1;
+        let n = len.next_power_of_two();
+        let fa = self.transform(a, n);
+        let fb = self.transform(b, n);
+        self.pointwise_inverse(&fa, &fb, len)
     }
-    pub fn convolve(&self, a: &[i64], b: &[i64]) -> Vec<i64> {
-        let mut a = a.to_vec();
-        let mut b = b.to_vec();
-        
-        let mut n = 1;
-        while n < a.len() + b.len() {
-            n <<= 1;
+    /// 固定したaと何度も掛け算する時用に変換を取っておく。
+    /// 長さmax_other_len以下の相手と掛けられる。
+    pub fn prepare(&self, a: &[i64], max_other_len: usize) -> PreparedOperand {
+        let n = (a.len() + max_other_len).next_power_of_two();
+        PreparedOperand {
+            len: a.len(),
+            data: self.transform(a, n),
         }
-        a.resize(n, 0);
-        b.resize(n, 0);
-
-        self.ntt(&mut a, n);
-        self.ntt(&mut b, n);
-
-        let mut c = vec![0;n];
-        for i in 0..n {
-            c[i] = (a[i] * b[i]) % self.mo;
+    }
+    pub fn convolve_prepared(&self, fa: &PreparedOperand, b: &[i64]) -> Vec<i64> {
+        if fa.len == 0 || b.is_empty() {
+            return vec![];
         }
+        let len = fa.len + b.len() - 1;
+        assert!(len <= fa.data.len());
+        let fb = self.transform(b, fa.data.len());
+        self.pointwise_inverse(&fa.data, &fb, len)
+    }
+}
+#[snippet("NTT")]
+pub struct PreparedOperand {
+    len: usize,
+    data: Vec<u32>,
+}
 
-        self.intt(&mut c, n);
-        c
+#[cfg(test)]
+fn naive_convolve(a: &[i64], b: &[i64], mo: i64) -> Vec<i64> {
+    let mut c = vec![0; a.len()+b.len()-1];
+    for i in 0..a.len() {
+        for j in 0..b.len() {
+            c[i+j] = (c[i+j] + a[i] * b[j]) % mo;
+        }
+    }
+    c
+}
+#[test]
+fn test_ntt_convolve() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for &mo in &[998_244_353, 754_974_721, 167_772_161, 469_762_049, 1_224_736_769, 2_013_265_921, 7_340_033] {
+        let ntt = NTT::new(mo);
+        for &(n, m) in &[(1, 1), (1, 7), (2, 2), (100, 77), (513, 512)] {
+            let a: Vec<i64> = (0..n).map(|_| rand.rand(mo as u64) as i64).collect();
+            let b: Vec<i64> = (0..m).map(|_| rand.rand(mo as u64) as i64).collect();
+            assert_eq!(ntt.convolve(&a, &b), naive_convolve(&a, &b, mo));
+        }
+    }
+}
+#[test]
+fn test_ntt_with_reduction() {
+    use crate::reduction::Barrett;
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    let mo = 998_244_353;
+    let a: Vec<i64> = (0..300).map(|_| rand.rand(mo as u64) as i64).collect();
+    let b: Vec<i64> = (0..200).map(|_| rand.rand(mo as u64) as i64).collect();
+    let ntt = NTT::with_reduction(Barrett::new(mo as u32));
+    assert_eq!(ntt.convolve(&a, &b), naive_convolve(&a, &b, mo));
+}
+#[test]
+fn test_ntt_table_cache() {
+    use crate::reduction::Barrett;
+    let mo = 998_244_353;
+    let t1 = ntt_table(mo);
+    let t2 = ntt_table(mo);
+    assert!(std::rc::Rc::ptr_eq(&t1, &t2));
+    // 同じ表から別のReductionのNTTを作っても正しく計算できる
+    let a = vec![1, 2, 3];
+    let b = vec![4, 5];
+    assert_eq!(NTT::new(mo as i64).convolve(&a, &b), vec![4, 13, 22, 15]);
+    assert_eq!(NTT::with_reduction(Barrett::new(mo)).convolve(&a, &b), vec![4, 13, 22, 15]);
+}
+#[test]
+fn test_ntt_prepared() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    let mo = 998_244_353;
+    let ntt = NTT::new(mo);
+    let a: Vec<i64> = (0..50).map(|_| rand.rand(mo as u64) as i64).collect();
+    let fa = ntt.prepare(&a, 100);
+    for &m in &[1, 10, 99, 100] {
+        let b: Vec<i64> = (0..m).map(|_| rand.rand(mo as u64) as i64).collect();
+        assert_eq!(ntt.convolve_prepared(&fa, &b), naive_convolve(&a, &b, mo));
     }
 }
+
 pub fn ntt_multiply_naive(a: &[i64], b: &[i64], mo: i64) -> Vec<i64> {
     let mut a = a.to_vec();
     let mut b = b.to_vec();
@@ -141,6 +284,17 @@ pub fn ntt_multiply_naive(a: &[i64], b: &[i64], mo: i64) -> Vec<i64> {
     res
 }
 #[snippet("NTT")]
+const NTT3_PRIMES: [i64; 3] = [167772161, 469762049, 1224736769];
+#[snippet("NTT")]
+thread_local! {
+    /// 回転因子の表を呼び出しごとに作り直さないように使い回す
+    static NTT3: [NTT; 3] = [
+        NTT::new(NTT3_PRIMES[0]),
+        NTT::new(NTT3_PRIMES[1]),
+        NTT::new(NTT3_PRIMES[2]),
+    ];
+}
+#[snippet("NTT")]
 pub fn ntt_multiply(a: &[i64], b: &[i64], mo: i64) -> Vec<i64> {
     let mut a = a.to_vec();
     let mut b = b.to_vec();
@@ -152,17 +306,15 @@ pub fn ntt_multiply(a: &[i64], b: &[i64], mo: i64) -> Vec<i64> {
     for i in 0..m {
         b[i] %= mo;
     }
-    let ntt1 = NTT::new(167772161);
-    let ntt2 = NTT::new(469762049);
-    let ntt3 = NTT::new(1224736769);
+    let (x, y, z) = NTT3.with(|ntt| (
+        ntt[0].convolve(&a, &b),
+        ntt[1].convolve(&a, &b),
+        ntt[2].convolve(&a, &b),
+    ));
 
-    let x = ntt1.convolve(&a, &b);
-    let y = ntt2.convolve(&a, &b);
-    let z = ntt3.convolve(&a, &b);
-
-    let m1 = ntt1.mo;
-    let m2 = ntt2.mo;
-    let m3 = ntt3.mo;
+    let m1 = NTT3_PRIMES[0];
+    let m2 = NTT3_PRIMES[1];
+    let m3 = NTT3_PRIMES[2];
     let m1_inv_m2 = modinv(m1, m2);
     let m12_inv_m3 = modinv(m1 * m2, m3);
     let m12_mod = (m1 * m2) % mo;
@@ -186,6 +338,95 @@ pub fn ntt_multiply(a: &[i64], b: &[i64], mo: i64) -> Vec<i64> {
     res
 }
 
+/// 4つの素数で畳み込んでGarnerで復元する。
+/// 係数は非負で、結果の各項が積 (約9.4e36) 未満であれば正確な値になる。
+#[snippet("ntt_multiply_exact")]
+const EXACT_PRIMES: [u32; 4] = [2013265921, 1811939329, 2113929217, 1224736769];
+#[snippet("ntt_multiply_exact")]
+thread_local! {
+    static NTT4: Vec<NTT> = EXACT_PRIMES.iter().map(|&p| NTT::new(p as i64)).collect();
+}
+#[snippet("ntt_multiply_exact")]
+pub fn ntt_multiply_exact(a: &[u64], b: &[u64]) -> Vec<u128> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let k = EXACT_PRIMES.len();
+    let ms: Vec<u64> = EXACT_PRIMES.iter().map(|&p| p as u64).collect();
+    let rs: Vec<Vec<i64>> = NTT4.with(|ntt| {
+        (0..k).map(|t| {
+            let am: Vec<i64> = a.iter().map(|&x| (x % ms[t]) as i64).collect();
+            let bm: Vec<i64> = b.iter().map(|&x| (x % ms[t]) as i64).collect();
+            ntt[t].convolve(&am, &bm)
+        }).collect()
+    });
+    // coef_inv[t] = (m_0 m_1 ... m_{t-1})^{-1} mod m_t
+    let mut coef_inv = vec![1; k];
+    for t in 0..k {
+        let mut c = 1;
+        for j in 0..t {
+            c = c * ms[j] % ms[t];
+        }
+        coef_inv[t] = modinv(c as i64, ms[t] as i64) as u64;
+    }
+    let len = a.len() + b.len() - 1;
+    let mut res = vec![0; len];
+    for i in 0..len {
+        // x = v_0 + v_1 m_0 + v_2 m_0 m_1 + ...
+        // cons[j] = (ここまでの和) mod m_j
+        let mut cons = vec![0; k];
+        let mut x: u128 = 0;
+        let mut mul: u128 = 1;
+        for t in 0..k {
+            let v = (rs[t][i] as u64 + ms[t] - cons[t]) % ms[t] * coef_inv[t] % ms[t];
+            x += v as u128 * mul;
+            mul *= ms[t] as u128;
+            for j in t+1..k {
+                cons[j] = (x % ms[j] as u128) as u64;
+            }
+        }
+        res[i] = x;
+    }
+    res
+}
+#[test]
+fn test_ntt_multiply_exact() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    fn naive(a: &[u64], b: &[u64]) -> Vec<u128> {
+        let mut c = vec![0; a.len()+b.len()-1];
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                c[i+j] += a[i] as u128 * b[j] as u128;
+            }
+        }
+        c
+    }
+    for &(n, m, max) in &[(1, 1, 10), (300, 200, 1_000_000_000_000_000), (3, 4, 1_000_000_000_000_000_000)] {
+        let a: Vec<u64> = (0..n).map(|_| rand.rand(max)).collect();
+        let b: Vec<u64> = (0..m).map(|_| rand.rand(max)).collect();
+        assert_eq!(ntt_multiply_exact(&a, &b), naive(&a, &b));
+    }
+    let a = vec![999_999_999_999_999_999; 4];
+    assert_eq!(ntt_multiply_exact(&a, &a)[3], 4 * 999_999_999_999_999_999u128 * 999_999_999_999_999_999u128);
+}
+
+#[snippet("ntt_multiply_mod")]
+pub fn ntt_multiply_mod<M: Modulus>(a: &[ModInt<M>], b: &[ModInt<M>]) -> Vec<ModInt<M>> {
+    let a: Vec<i64> = a.iter().map(|x| x.0).collect();
+    let b: Vec<i64> = b.iter().map(|x| x.0).collect();
+    ntt_multiply(&a, &b, M::modulus()).into_iter().map(ModInt::new).collect()
+}
+#[test]
+fn test_ntt_multiply_mod() {
+    use crate::modint::modular::{Mod998, Mod1e9_7};
+    let a: Vec<Mod998> = vec![1.into(), 2.into()];
+    let b: Vec<Mod998> = vec![1.into(), 2.into(), 998_244_352.into()];
+    assert_eq!(ntt_multiply_mod(&a, &b), vec![1.into(), 4.into(), 3.into(), (-2).into()]);
+    let c: Vec<Mod1e9_7> = vec![1_000_000_006.into(); 3];
+    assert_eq!(ntt_multiply_mod(&c, &c), vec![1.into(), 2.into(), 3.into(), 2.into(), 1.into()]);
+}
+
 #[test]
 fn test_ntt_multiply() {
     fn ten(n: usize) -> i64 {
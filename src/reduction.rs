@@ -0,0 +1,255 @@
+use cargo_snippet::snippet;
+
+/// 剰余乗算の高速化
+///
+/// 実行時に決まる法での % は除算命令になるため遅い。
+/// 以下の二つは乗算とシフトだけで剰余を計算する。
+///
+/// Barrett:
+/// u32の任意の法に使える。値はそのまま[0,m)で持つ。
+/// im = ceil(2^64/m) を前計算しておき、
+/// z/m を (z*im)>>64 で近似する。
+/// 近似の誤差が1以内に収まるのは 2 <= m < 2^31 の時なので、
+/// それ以外(m = 1 と m >= 2^31)では im = 0 として普通の%で計算する。
+///
+/// Montgomery:
+/// 奇数の法(m < 2^31)に使える。値はaR (R=2^32)の形で持つ。
+/// 入力と出力で変換が必要なので、NTTやpowのように
+/// 変換後に何度も掛け算する場合に向いている。
+
+#[snippet("Reduction")]
+#[snippet("Barrett")]
+#[snippet("Montgomery")]
+#[snippet("NTT")]
+#[snippet("modint")]
+#[snippet("ModComb")]
+pub trait Reduction {
+    fn modulus(&self) -> u32;
+    /// x (< m) を内部表現に変換する
+    fn to_repr(&self, x: u32) -> u32;
+    fn from_repr(&self, x: u32) -> u32;
+    /// 内部表現同士の積
+    fn mul(&self, a: u32, b: u32) -> u32;
+    /// 普通の値(< m)同士の積を普通の値で返す
+    fn mul_plain(&self, a: u32, b: u32) -> u32 {
+        self.from_repr(self.mul(self.to_repr(a), self.to_repr(b)))
+    }
+    fn add(&self, a: u32, b: u32) -> u32 {
+        // 法が2^31以上だとu32で溢れる
+        let s = a as u64 + b as u64;
+        let m = self.modulus() as u64;
+        (if s >= m { s - m } else { s }) as u32
+    }
+    fn sub(&self, a: u32, b: u32) -> u32 {
+        if a >= b { a - b } else { a.wrapping_sub(b).wrapping_add(self.modulus()) }
+    }
+    /// 内部表現のままa^n
+    fn pow(&self, a: u32, n: u64) -> u32 {
+        let mut res = self.to_repr(1);
+        let mut x = a;
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 {
+                res = self.mul(res, x);
+            }
+            x = self.mul(x, x);
+            n >>= 1;
+        }
+        res
+    }
+}
+
+#[snippet("Barrett")]
+#[snippet("NTT")]
+#[snippet("modint")]
+#[snippet("ModComb")]
+#[derive(Debug, Clone, Copy)]
+pub struct Barrett {
+    pub m: u32,
+    pub im: u64,
+}
+#[snippet("Barrett")]
+#[snippet("NTT")]
+#[snippet("modint")]
+#[snippet("ModComb")]
+impl Barrett {
+    pub const fn new(m: u32) -> Barrett {
+        assert!(m >= 1);
+        Barrett {
+            m: m,
+            im: if m >= 2 && m < 1 << 31 { (!0u64 / m as u64).wrapping_add(1) } else { 0 },
+        }
+    }
+    /// z mod m (z < m^2)
+    pub fn reduce(&self, z: u64) -> u32 {
+        if self.im == 0 {
+            return (z % self.m as u64) as u32;
+        }
+        let x = ((z as u128 * self.im as u128) >> 64) as u64;
+        let v = z.wrapping_sub(x.wrapping_mul(self.m as u64)) as u32;
+        if self.m <= v { v.wrapping_add(self.m) } else { v }
+    }
+}
+#[snippet("Barrett")]
+#[snippet("NTT")]
+#[snippet("modint")]
+#[snippet("ModComb")]
+impl Reduction for Barrett {
+    fn modulus(&self) -> u32 {
+        self.m
+    }
+    fn to_repr(&self, x: u32) -> u32 {
+        // m = 1 の時のため
+        self.reduce(x as u64)
+    }
+    fn from_repr(&self, x: u32) -> u32 {
+        x
+    }
+    fn mul(&self, a: u32, b: u32) -> u32 {
+        self.reduce(a as u64 * b as u64)
+    }
+    fn mul_plain(&self, a: u32, b: u32) -> u32 {
+        self.reduce(a as u64 * b as u64)
+    }
+}
+
+#[snippet("Montgomery")]
+#[snippet("NTT")]
+#[derive(Debug, Clone, Copy)]
+pub struct Montgomery {
+    pub m: u32,
+    /// m * neg_inv = -1 (mod 2^32)
+    neg_inv: u32,
+    /// R^2 mod m
+    r2: u32,
+}
+#[snippet("Montgomery")]
+#[snippet("NTT")]
+impl Montgomery {
+    pub const fn new(m: u32) -> Montgomery {
+        assert!(m & 1 == 1 && m < 1 << 31);
+        // ニュートン法で毎回正しいビット数が倍になる
+        let mut inv = m;
+        let mut i = 0;
+        while i < 4 {
+            inv = inv.wrapping_mul(2u32.wrapping_sub(m.wrapping_mul(inv)));
+            i += 1;
+        }
+        Montgomery {
+            m: m,
+            neg_inv: inv.wrapping_neg(),
+            r2: ((1u128 << 64) % m as u128) as u32,
+        }
+    }
+    /// t R^{-1} mod m (t < mR)
+    pub fn reduce(&self, t: u64) -> u32 {
+        let u = (t as u32).wrapping_mul(self.neg_inv);
+        let v = ((t + u as u64 * self.m as u64) >> 32) as u32;
+        if v >= self.m { v - self.m } else { v }
+    }
+}
+#[snippet("Montgomery")]
+#[snippet("NTT")]
+impl Reduction for Montgomery {
+    fn modulus(&self) -> u32 {
+        self.m
+    }
+    fn to_repr(&self, x: u32) -> u32 {
+        self.reduce(x as u64 * self.r2 as u64)
+    }
+    fn from_repr(&self, x: u32) -> u32 {
+        self.reduce(x as u64)
+    }
+    fn mul(&self, a: u32, b: u32) -> u32 {
+        self.reduce(a as u64 * b as u64)
+    }
+    /// a * bR * R^{-1} = ab なので、片方だけ変換すればよい
+    fn mul_plain(&self, a: u32, b: u32) -> u32 {
+        self.mul(a, self.to_repr(b))
+    }
+}
+
+#[cfg(test)]
+fn check_reduction<R: Reduction>(red: &R) {
+    use crate::xorshift::Xorshift;
+    let m = red.modulus() as u64;
+    let mut rand = Xorshift::new();
+    let mut edge = vec![0, 1 % m, m-1, m/2];
+    for _ in 0..1000 {
+        edge.push(rand.rand(m));
+    }
+    for &a in &edge {
+        for &b in &edge[..10] {
+            let x = red.to_repr(a as u32);
+            let y = red.to_repr(b as u32);
+            assert_eq!(red.from_repr(x) as u64, a);
+            assert_eq!(red.from_repr(red.mul(x, y)) as u64, a * b % m);
+            assert_eq!(red.from_repr(red.add(x, y)) as u64, (a + b) % m);
+            assert_eq!(red.from_repr(red.sub(x, y)) as u64, (a + m - b) % m);
+            assert_eq!(red.mul_plain(a as u32, b as u32) as u64, a * b % m);
+        }
+    }
+    let x = red.to_repr(3 % m as u32);
+    let mut expected = 1 % m;
+    for _ in 0..1000 {
+        expected = expected * 3 % m;
+    }
+    assert_eq!(red.from_repr(red.pow(x, 1000)) as u64, expected);
+}
+#[test]
+fn test_barrett() {
+    for &m in &[1, 2, 3, 1000, 998_244_353, 1_000_000_007, 2_147_483_647, 2_147_483_648, 2_147_483_659, 4_294_967_291, std::u32::MAX] {
+        check_reduction(&Barrett::new(m));
+    }
+    assert_eq!(Barrett::new(1).reduce(5), 0);
+}
+#[test]
+fn test_montgomery() {
+    for &m in &[3, 1001, 167_772_161, 998_244_353, 1_000_000_007, 2_147_483_647] {
+        check_reduction(&Montgomery::new(m));
+    }
+}
+
+#[cfg(test)]
+const BENCH_N: u64 = 1_000_000;
+#[cfg(test)]
+fn bench_modulus() -> u32 {
+    // 定数畳み込みされないように実行時の値にする
+    test::black_box(998_244_353)
+}
+
+#[bench]
+fn bench_mulmod_naive(b: &mut test::Bencher) {
+    let m = bench_modulus() as u64;
+    b.iter(|| {
+        let mut x = 1;
+        for i in 0..BENCH_N {
+            x = x * (i + 2) % m;
+        }
+        x
+    })
+}
+
+#[bench]
+fn bench_mulmod_barrett(b: &mut test::Bencher) {
+    let red = Barrett::new(bench_modulus());
+    b.iter(|| {
+        let mut x = 1;
+        for i in 0..BENCH_N {
+            x = red.mul(x, i as u32 + 2);
+        }
+        x
+    })
+}
+
+#[bench]
+fn bench_mulmod_montgomery(b: &mut test::Bencher) {
+    let red = Montgomery::new(bench_modulus());
+    b.iter(|| {
+        let mut x = red.to_repr(1);
+        for i in 0..BENCH_N {
+            x = red.mul(x, i as u32 + 2);
+        }
+        red.from_repr(x)
+    })
+}
@@ -18,7 +18,10 @@ pub trait Monoid {
 #[snippet("SEG")]
 #[allow(dead_code)]
 pub struct SEG<M: Monoid> {
+    /// 2の累乗に切り上げた葉の数
     pub n: usize,
+    /// 元の列の長さ
+    pub len: usize,
     pub buf: Vec<M::T>,
 }
 
@@ -30,10 +33,25 @@ impl<M: Monoid> SEG<M> {
         while m < n { m *= 2; }
         SEG {
             n: m,
+            len: n,
             buf: vec![M::id().clone(); 2 * m],
         }
     }
 
+    #[allow(dead_code)]
+    #[doc = "O(N)"]
+    pub fn from_vec(v: Vec<M::T>) -> SEG<M> {
+        let mut seg = SEG::new(v.len());
+        let n = seg.n;
+        for (i, x) in v.into_iter().enumerate() {
+            seg.buf[n + i] = x;
+        }
+        for k in (1..n).rev() {
+            seg.buf[k] = M::op(&seg.buf[k*2], &seg.buf[k*2+1]);
+        }
+        seg
+    }
+
     #[allow(dead_code)]
     pub fn update(&mut self, k: usize, a: M::T) {
         let mut k = k + self.n;
@@ -69,11 +87,80 @@ impl<M: Monoid> SEG<M> {
     pub fn query(&self, a: usize, b: usize) -> M::T {
         self.do_query(a,b,1,0,self.n)
     }
+
+    /// f(query(l,r))がtrueとなる最大のr。
+    /// fは単調で、f(id)=trueであること。
+    /// 末尾までtrueなら元の列の長さlenを返す。
+    #[allow(dead_code)]
+    pub fn max_right<F: Fn(&M::T) -> bool>(&self, l: usize, f: F) -> usize {
+        assert!(l <= self.len);
+        assert!(f(&M::id()));
+        if l == self.len {
+            return self.len;
+        }
+        let mut l = l + self.n;
+        let mut sm = M::id();
+        loop {
+            while l % 2 == 0 {
+                l >>= 1;
+            }
+            if !f(&M::op(&sm, &self.buf[l])) {
+                while l < self.n {
+                    l *= 2;
+                    let x = M::op(&sm, &self.buf[l]);
+                    if f(&x) {
+                        sm = x;
+                        l += 1;
+                    }
+                }
+                return std::cmp::min(l - self.n, self.len);
+            }
+            sm = M::op(&sm, &self.buf[l]);
+            l += 1;
+            if l & l.wrapping_neg() == l {
+                return self.len;
+            }
+        }
+    }
+
+    /// f(query(l,r))がtrueとなる最小のl。
+    /// fは単調で、f(id)=trueであること。
+    #[allow(dead_code)]
+    pub fn min_left<F: Fn(&M::T) -> bool>(&self, r: usize, f: F) -> usize {
+        assert!(r <= self.len);
+        assert!(f(&M::id()));
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.n;
+        let mut sm = M::id();
+        loop {
+            r -= 1;
+            while r > 1 && r % 2 == 1 {
+                r >>= 1;
+            }
+            if !f(&M::op(&self.buf[r], &sm)) {
+                while r < self.n {
+                    r = 2 * r + 1;
+                    let x = M::op(&self.buf[r], &sm);
+                    if f(&x) {
+                        sm = x;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.n;
+            }
+            sm = M::op(&self.buf[r], &sm);
+            if r & r.wrapping_neg() == r {
+                return 0;
+            }
+        }
+    }
 }
 
 #[snippet("SEG_SUM")]
 #[allow(dead_code)]
-struct SUM;
+pub(crate) struct SUM;
 #[snippet("SEG_SUM")]
 impl Monoid for SUM {
     type T = i64;
@@ -100,6 +187,36 @@ fn test_seg_sum() {
     assert_eq!(seg.query(2, 4), 7);
 }
 
+#[test]
+fn test_seg_max_right_min_left() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for &n in &[1, 2, 5, 8, 13] {
+        let v: Vec<i64> = (0..n).map(|_| rand.rand(10) as i64).collect();
+        let seg: SEG<SUM> = SEG::from_vec(v.clone());
+        for i in 0..n {
+            assert_eq!(seg.get(i), v[i]);
+        }
+        for k in 0..50 {
+            for l in 0..n+1 {
+                // 和がk以下である最大のr
+                let mut r = l;
+                while r < n && v[l..r+1].iter().sum::<i64>() <= k {
+                    r += 1;
+                }
+                assert_eq!(seg.max_right(l, |&x| x <= k), r);
+            }
+            for r in 0..n+1 {
+                let mut l = r;
+                while l > 0 && v[l-1..r].iter().sum::<i64>() <= k {
+                    l -= 1;
+                }
+                assert_eq!(seg.min_left(r, |&x| x <= k), l);
+            }
+        }
+    }
+}
+
 #[snippet("SEG_MIN")]
 #[allow(dead_code)]
 struct MIN;
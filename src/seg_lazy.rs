@@ -12,9 +12,12 @@ use cargo_snippet::snippet;
 /// 計算量:
 /// update l r f: O(logN)
 /// query l r: O(logN)
+/// set i x, get i: O(logN)
+/// max_right l f, min_left r f: O(logN)
+/// from_vec: O(N)
 
 #[snippet("SEG_LAZY")]
-trait SEGLazyImpl {
+pub trait SEGLazyImpl {
     type Monoid: Copy;
     type F: Copy + PartialEq;
     fn e() -> Self::Monoid;
@@ -29,7 +32,10 @@ trait SEGLazyImpl {
 
 #[snippet("SEG_LAZY")]
 struct SEGLazy<T: SEGLazyImpl> {
+    /// 2の累乗に切り上げた葉の数
     n: usize,
+    /// 元の列の長さ
+    len: usize,
     data: Vec<T::Monoid>,
     lazy: Vec<T::F>,
 }
@@ -41,10 +47,23 @@ impl <T: SEGLazyImpl> SEGLazy<T> {
         while m < n { m *= 2; }
         SEGLazy {
             n: m,
+            len: n,
             data: vec![init; m*2],
             lazy: vec![T::id(); m*2],
         }
     }
+    /// 葉をvで初期化する。余った葉はe()
+    pub fn from_vec(v: Vec<T::Monoid>) -> Self {
+        let mut seg = Self::new(v.len(), T::e());
+        let n = seg.n;
+        for (i, x) in v.into_iter().enumerate() {
+            seg.data[n+i] = x;
+        }
+        for k in (1..n).rev() {
+            seg.data[k] = T::op(seg.data[2*k+0], seg.data[2*k+1]);
+        }
+        seg
+    }
     fn propagate(&mut self, k: usize) {
         if self.lazy[k] != T::id() {
             if k < self.n {
@@ -94,6 +113,89 @@ impl <T: SEGLazyImpl> SEGLazy<T> {
         let n = self.n;
         self.do_query(l, r, 1, 0, n)
     }
+    fn do_set(&mut self, i: usize, x: T::Monoid, k: usize, l: usize, r: usize) {
+        self.propagate(k);
+        if r - l == 1 {
+            self.data[k] = x;
+            return;
+        }
+        let mid = (l+r)>>1;
+        if i < mid {
+            self.do_set(i, x, 2*k+0, l, mid);
+            self.propagate(2*k+1);
+        } else {
+            self.propagate(2*k+0);
+            self.do_set(i, x, 2*k+1, mid, r);
+        }
+        self.data[k] = T::op(self.data[2*k+0], self.data[2*k+1]);
+    }
+    pub fn set(&mut self, i: usize, x: T::Monoid) {
+        assert!(i < self.len);
+        let n = self.n;
+        self.do_set(i, x, 1, 0, n)
+    }
+    pub fn get(&mut self, i: usize) -> T::Monoid {
+        assert!(i < self.len);
+        self.query(i, i+1)
+    }
+    fn do_max_right<F: Fn(T::Monoid) -> bool>(&mut self, a: usize, f: &F, acc: &mut T::Monoid, k: usize, l: usize, r: usize) -> Option<usize> {
+        self.propagate(k);
+        if r <= a {
+            return None;
+        }
+        if a <= l {
+            let x = T::op(*acc, self.data[k]);
+            if f(x) {
+                *acc = x;
+                return None;
+            }
+            if r - l == 1 {
+                return Some(l);
+            }
+        }
+        let mid = (l+r)>>1;
+        self.do_max_right(a, f, acc, 2*k+0, l, mid)
+            .or_else(|| self.do_max_right(a, f, acc, 2*k+1, mid, r))
+    }
+    /// f(query(l,r))がtrueとなる最大のr。
+    /// fは単調で、f(e())=trueであること。
+    /// 末尾までtrueなら元の列の長さlenを返す。
+    pub fn max_right<F: Fn(T::Monoid) -> bool>(&mut self, l: usize, f: F) -> usize {
+        assert!(l <= self.len);
+        assert!(f(T::e()));
+        let n = self.n;
+        let mut acc = T::e();
+        let r = self.do_max_right(l, &f, &mut acc, 1, 0, n).unwrap_or(n);
+        std::cmp::min(r, self.len)
+    }
+    fn do_min_left<F: Fn(T::Monoid) -> bool>(&mut self, b: usize, f: &F, acc: &mut T::Monoid, k: usize, l: usize, r: usize) -> Option<usize> {
+        self.propagate(k);
+        if b <= l {
+            return None;
+        }
+        if r <= b {
+            let x = T::op(self.data[k], *acc);
+            if f(x) {
+                *acc = x;
+                return None;
+            }
+            if r - l == 1 {
+                return Some(r);
+            }
+        }
+        let mid = (l+r)>>1;
+        self.do_min_left(b, f, acc, 2*k+1, mid, r)
+            .or_else(|| self.do_min_left(b, f, acc, 2*k+0, l, mid))
+    }
+    /// f(query(l,r))がtrueとなる最小のl。
+    /// fは単調で、f(e())=trueであること。
+    pub fn min_left<F: Fn(T::Monoid) -> bool>(&mut self, r: usize, f: F) -> usize {
+        assert!(r <= self.len);
+        assert!(f(T::e()));
+        let n = self.n;
+        let mut acc = T::e();
+        self.do_min_left(r, &f, &mut acc, 1, 0, n).unwrap_or(0)
+    }
 }
 
 #[snippet("SEG_LAZY_MAX_RUQ")]
@@ -193,7 +295,7 @@ impl SEGLazyImpl for MAX_RAQ {
 }
 
 #[snippet("SEG_LAZY_MIN_RAQ")]
-struct MIN_RAQ;
+pub(crate) struct MIN_RAQ;
 #[snippet("SEG_LAZY_MIN_RAQ")]
 impl SEGLazyImpl for MIN_RAQ { 
     type Monoid = i64;
@@ -224,4 +326,111 @@ fn test_rmq_raq() { // DSL_2_H
     seg.update(3,6,3);
     assert_eq!(seg.query(3,5),1);
     assert_eq!(seg.query(0,6),-1);
-}
\ No newline at end of file
+}
+
+#[snippet("SEG_LAZY_SUM_RAQ")]
+#[allow(non_camel_case_types)]
+pub(crate) struct SUM_RAQ;
+#[snippet("SEG_LAZY_SUM_RAQ")]
+impl SEGLazyImpl for SUM_RAQ {
+    /// (和, 区間の長さ)。from_vecで葉を(x, 1)として作ること
+    type Monoid = (i64, i64);
+    type F = i64;
+    fn e() -> Self::Monoid {
+        (0, 0)
+    }
+    fn id() -> Self::F {
+        0
+    }
+    fn op(x: Self::Monoid, y: Self::Monoid) -> Self::Monoid {
+        (x.0 + y.0, x.1 + y.1)
+    }
+    fn ap(f: Self::F, x: Self::Monoid) -> Self::Monoid {
+        (x.0 + f * x.1, x.1)
+    }
+    fn compose(f: Self::F, g: Self::F) -> Self::F {
+        g + f
+    }
+}
+#[test]
+fn test_sum_raq_prefix_search() {
+    let mut seg: SEGLazy<SUM_RAQ> = SEGLazy::from_vec(vec![(1, 1); 10]);
+    seg.update(2, 5, 2); // [1,1,3,3,3,1,1,1,1,1]
+    assert_eq!(seg.query(0, 10), (16, 10));
+    // 累積和が7を超える最初の位置
+    assert_eq!(seg.max_right(0, |x| x.0 <= 7), 3);
+    assert_eq!(seg.max_right(0, |x| x.0 <= 8), 4);
+    seg.set(0, (10, 1));
+    assert_eq!(seg.get(0), (10, 1));
+    assert_eq!(seg.get(3), (3, 1));
+    assert_eq!(seg.max_right(0, |x| x.0 <= 7), 0);
+    assert_eq!(seg.min_left(10, |x| x.0 <= 5), 5);
+    assert_eq!(seg.max_right(5, |x| x.0 <= 100), 10);
+    assert_eq!(seg.max_right(10, |x| x.0 <= 0), 10);
+}
+#[should_panic]
+#[test]
+fn test_seg_lazy_set_out_of_range() {
+    // 長さ5なので葉は8個あるが、5以降には書き込めない
+    let mut seg: SEGLazy<SUM_RAQ> = SEGLazy::from_vec(vec![(1, 1); 5]);
+    seg.set(5, (1, 1));
+}
+#[test]
+fn test_seg_lazy_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for &n in &[1, 3, 8, 13] {
+        let mut v: Vec<i64> = (0..n).map(|_| rand.rand(10) as i64).collect();
+        let mut sum: SEGLazy<SUM_RAQ> = SEGLazy::from_vec(v.iter().map(|&x| (x, 1)).collect());
+        let mut min: SEGLazy<MIN_RAQ> = SEGLazy::from_vec(v.clone());
+        for _ in 0..1000 {
+            let l = rand.rand(n as u64 + 1) as usize;
+            let r = l + rand.rand((n - l) as u64 + 1) as usize;
+            match rand.rand(5) {
+                0 => {
+                    let x = rand.rand(5) as i64;
+                    for i in l..r {
+                        v[i] += x;
+                    }
+                    sum.update(l, r, x);
+                    min.update(l, r, x);
+                },
+                1 if l < n => {
+                    let x = rand.rand(10) as i64;
+                    v[l] = x;
+                    sum.set(l, (x, 1));
+                    min.set(l, x);
+                },
+                2 => {
+                    assert_eq!(sum.query(l, r).0, v[l..r].iter().sum::<i64>());
+                    assert_eq!(min.query(l, r), v[l..r].iter().cloned().min().unwrap_or(std::i64::MAX));
+                    if l < n {
+                        assert_eq!(sum.get(l).0, v[l]);
+                        assert_eq!(min.get(l), v[l]);
+                    }
+                },
+                3 => {
+                    let k = rand.rand(30) as i64;
+                    let mut r = l;
+                    while r < n && v[l..r+1].iter().sum::<i64>() <= k {
+                        r += 1;
+                    }
+                    assert_eq!(sum.max_right(l, |x| x.0 <= k), r);
+                    let mut r = l;
+                    while r < n && v[r] >= k / 3 {
+                        r += 1;
+                    }
+                    assert_eq!(min.max_right(l, |x| x >= k / 3), r);
+                },
+                _ => {
+                    let k = rand.rand(30) as i64;
+                    let mut l = r;
+                    while l > 0 && v[l-1..r].iter().sum::<i64>() <= k {
+                        l -= 1;
+                    }
+                    assert_eq!(sum.min_left(r, |x| x.0 <= k), l);
+                },
+            }
+        }
+    }
+}
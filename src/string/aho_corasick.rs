@@ -0,0 +1,235 @@
+use cargo_snippet::snippet;
+use crate::dfa::Dfa;
+use std::collections::BTreeMap;
+
+/// Aho-Corasick
+///
+/// 複数のパターンからトライを作り、
+/// KMPの失敗関数をトライの上に拡張したもの(fail)を張る。
+/// fail[v]はvの表す文字列の真のsuffixのうち、トライに含まれる最長のもの。
+///
+/// 構築計算量 O(Σ|P| log σ)
+///
+/// 文字列Sから全パターンを検索
+/// 計算量 O(|S| log σ + マッチ数)
+///
+/// Dfaを実装しているので、dfa::countで
+/// 「どのパターンも含まない長さNの文字列」を数えられる。
+
+#[snippet("AhoCorasick")]
+pub struct AhoCorasick {
+    /// トライの遷移
+    pub goto: Vec<BTreeMap<u64, usize>>,
+    pub fail: Vec<usize>,
+    /// そのノードでちょうど終わるパターンのid
+    pub out: Vec<Vec<usize>>,
+    /// failを辿って最初にoutが空でないノード。なければ0
+    pub out_link: Vec<usize>,
+    /// いずれかのパターンをsuffixに持つか
+    pub matched: Vec<bool>,
+    pub pattern_len: Vec<usize>,
+}
+#[snippet("AhoCorasick")]
+impl AhoCorasick {
+    /// パターンは空でないこと
+    pub fn new(patterns: &[Vec<u64>]) -> Self {
+        let mut goto = vec![BTreeMap::new()];
+        let mut out = vec![vec![]];
+        for (id, pat) in patterns.iter().enumerate() {
+            assert!(!pat.is_empty());
+            let mut v = 0;
+            for &c in pat {
+                v = match goto[v].get(&c) {
+                    Some(&u) => u,
+                    None => {
+                        goto.push(BTreeMap::new());
+                        out.push(vec![]);
+                        let u = goto.len() - 1;
+                        goto[v].insert(c, u);
+                        u
+                    },
+                };
+            }
+            out[v].push(id);
+        }
+        let n = goto.len();
+        let mut fail = vec![0; n];
+        let mut out_link = vec![0; n];
+        let mut matched: Vec<bool> = out.iter().map(|o| !o.is_empty()).collect();
+        // BFS順に浅いノードから決めていく
+        let mut q = std::collections::VecDeque::new();
+        q.push_back(0);
+        while let Some(v) = q.pop_front() {
+            for (&c, &u) in &goto[v] {
+                if v != 0 {
+                    let mut f = fail[v];
+                    while f != 0 && !goto[f].contains_key(&c) {
+                        f = fail[f];
+                    }
+                    fail[u] = goto[f].get(&c).cloned().unwrap_or(0);
+                }
+                let f = fail[u];
+                out_link[u] = if out[f].is_empty() { out_link[f] } else { f };
+                matched[u] |= matched[f];
+                q.push_back(u);
+            }
+        }
+        AhoCorasick {
+            goto: goto,
+            fail: fail,
+            out: out,
+            out_link: out_link,
+            matched: matched,
+            pattern_len: patterns.iter().map(|p| p.len()).collect(),
+        }
+    }
+    pub fn size(&self) -> usize {
+        self.goto.len()
+    }
+    /// ノードvから文字cで進んだ先
+    pub fn next_node(&self, v: usize, c: u64) -> usize {
+        let mut v = v;
+        loop {
+            if let Some(&u) = self.goto[v].get(&c) {
+                return u;
+            }
+            if v == 0 {
+                return 0;
+            }
+            v = self.fail[v];
+        }
+    }
+    /// (パターンid, 終了位置)を全て返す。終了位置は開区間で、
+    /// s[end-|P|..end]がパターンに一致する
+    pub fn search(&self, s: &[u64]) -> Vec<(usize, usize)> {
+        let mut res = vec![];
+        let mut v = 0;
+        for (i, &c) in s.iter().enumerate() {
+            v = self.next_node(v, c);
+            let mut u = v;
+            while u != 0 {
+                for &id in &self.out[u] {
+                    res.push((id, i + 1));
+                }
+                u = self.out_link[u];
+            }
+        }
+        res
+    }
+}
+
+/// 状態はトライのノード。どれかのパターンを含んだらsize()に落ちる
+#[snippet("AhoCorasick")]
+impl Dfa for AhoCorasick {
+    type Alphabet = u64;
+    type State = usize;
+    fn init(&self) -> Self::State {
+        0
+    }
+    fn next(&self, s: Self::State, a: Self::Alphabet, _: usize) -> Self::State {
+        if s == self.size() {
+            return s;
+        }
+        let u = self.next_node(s, a);
+        if self.matched[u] { self.size() } else { u }
+    }
+    fn accept(&self, s: Self::State) -> bool {
+        s != self.size()
+    }
+}
+
+#[test]
+fn test_aho_corasick() {
+    let pats = vec![vec![1, 2], vec![2], vec![2, 3, 1], vec![1, 2, 3, 1, 2]];
+    let ac = AhoCorasick::new(&pats);
+    let mut res = ac.search(&[1, 2, 3, 1, 2, 3]);
+    res.sort();
+    assert_eq!(res, [(0, 2), (0, 5), (1, 2), (1, 5), (2, 4), (3, 5)]);
+}
+
+#[test]
+fn test_aho_corasick_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..100 {
+        let sigma = 1 + rand.rand(3);
+        let k = 1 + rand.rand(5) as usize;
+        let pats: Vec<Vec<u64>> = (0..k).map(|_| {
+            let m = 1 + rand.rand(4) as usize;
+            (0..m).map(|_| rand.rand(sigma)).collect()
+        }).collect();
+        let ac = AhoCorasick::new(&pats);
+        let n = rand.rand(50) as usize;
+        let s: Vec<u64> = (0..n).map(|_| rand.rand(sigma)).collect();
+        let mut res = ac.search(&s);
+        res.sort();
+        let mut expected = vec![];
+        for (id, p) in pats.iter().enumerate() {
+            for end in p.len()..n+1 {
+                if s[end-p.len()..end] == p[..] {
+                    expected.push((id, end));
+                }
+            }
+        }
+        expected.sort();
+        assert_eq!(res, expected);
+    }
+}
+
+#[test]
+fn test_aho_corasick_dfa_count() {
+    use crate::dfa::count;
+    use crate::xorshift::Xorshift;
+    // "11"を含まない二進列はフィボナッチ数
+    let ac = AhoCorasick::new(&[vec![1, 1]]);
+    assert_eq!(count(&ac, 10, &[0, 1], 1_000_000_007), 144);
+
+    let mut rand = Xorshift::new();
+    for _ in 0..30 {
+        let pats: Vec<Vec<u64>> = (0..3).map(|_| {
+            let m = 1 + rand.rand(3) as usize;
+            (0..m).map(|_| rand.rand(3)).collect()
+        }).collect();
+        let ac = AhoCorasick::new(&pats);
+        let n = 6;
+        let mut expected = 0;
+        for mut x in 0..3u32.pow(n as u32) {
+            let s: Vec<u64> = (0..n).map(|_| { let c = x % 3; x /= 3; c as u64 }).collect();
+            if pats.iter().all(|p| !s.windows(p.len()).any(|w| w == &p[..])) {
+                expected += 1;
+            }
+        }
+        assert_eq!(count(&ac, n, &[0, 1, 2], 1_000_000_007), expected);
+    }
+}
+
+#[test]
+fn test_aho_corasick_large() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    // パターン長の合計が1e6
+    let pats: Vec<Vec<u64>> = (0..1000).map(|_| (0..1000).map(|_| rand.rand(2)).collect()).collect();
+    let ac = AhoCorasick::new(&pats);
+    let mut s = pats[3].clone();
+    s.extend(pats[5].iter());
+    let res = ac.search(&s);
+    assert!(res.contains(&(3, 1000)));
+    assert!(res.contains(&(5, 2000)));
+}
+
+#[test]
+fn test_aho_corasick_large_alphabet() {
+    // 文字の種類数が大きくても構築はパターン長の合計に比例する
+    let n = 200_000;
+    let sym = |k: u64| k * 1_000_000_007 + 3;
+    let mut pats: Vec<Vec<u64>> = (0..n).map(|k| vec![sym(k)]).collect();
+    pats.push((0..n).map(sym).collect());
+    let ac = AhoCorasick::new(&pats);
+    assert_eq!(ac.size(), 2 * n as usize);
+    let s: Vec<u64> = (0..n).map(sym).collect();
+    let mut res = ac.search(&s);
+    res.sort();
+    let mut expected: Vec<(usize, usize)> = (0..n as usize).map(|k| (k, k + 1)).collect();
+    expected.push((n as usize, n as usize));
+    assert_eq!(res, expected);
+}
@@ -35,4 +35,203 @@ fn test_edit_distance() {
     let dp = edit_distance(s, t);
     dbg!(&dp);
     assert_eq!(dp[4][3], 2);
-}
\ No newline at end of file
+}
+/// 編集操作。添字はそれぞれsとtでの位置
+#[snippet("Hirschberg")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditOp {
+    /// s[i] == t[j]
+    Keep(usize, usize),
+    /// s[i]をt[j]に置き換える
+    Substitute(usize, usize),
+    /// s[i]を消す
+    Delete(usize),
+    /// t[j]を挿入する
+    Insert(usize),
+}
+
+/// 各操作のコスト。Keepは0
+#[snippet("Hirschberg")]
+#[derive(Debug, Clone, Copy)]
+pub struct EditCost {
+    pub insert: i64,
+    pub delete: i64,
+    pub substitute: i64,
+}
+
+/// Hirschbergのアルゴリズム
+///
+/// sを半分に分け、前半は前から、後半は後ろからDPの最後の行だけを求める。
+/// 二つを足して最小になるtの分割位置で分けて再帰する。
+/// 操作列まで復元しつつ、メモリはO(N+M)で済む。
+///
+/// 計算量 O(NM)
+/// 空間量 O(N+M)
+
+#[snippet("Hirschberg")]
+pub fn edit_alignment<T: Eq>(s: &[T], t: &[T], cost: &EditCost) -> (i64, Vec<EditOp>) {
+    let mut ops = vec![];
+    hirschberg_rec(s, t, 0, 0, cost, &mut ops);
+    let total = ops.iter().map(|op| match op {
+        EditOp::Keep(_, _) => 0,
+        EditOp::Substitute(_, _) => cost.substitute,
+        EditOp::Delete(_) => cost.delete,
+        EditOp::Insert(_) => cost.insert,
+    }).sum();
+    (total, ops)
+}
+
+/// s全体とtの各prefixとの編集コスト。revなら後ろから見る
+#[snippet("Hirschberg")]
+fn hirschberg_last_row<T: Eq>(s: &[T], t: &[T], cost: &EditCost, rev: bool) -> Vec<i64> {
+    let m = t.len();
+    let tt = |j: usize| if rev { &t[m-1-j] } else { &t[j] };
+    let mut dp: Vec<i64> = (0..m as i64 + 1).map(|j| j * cost.insert).collect();
+    for k in 0..s.len() {
+        let x = if rev { &s[s.len()-1-k] } else { &s[k] };
+        let mut diag = dp[0];
+        dp[0] += cost.delete;
+        for j in 0..m {
+            let change = if x == tt(j) { diag } else { diag + cost.substitute };
+            diag = dp[j+1];
+            dp[j+1] = min(change, min(dp[j+1] + cost.delete, dp[j] + cost.insert));
+        }
+    }
+    dp
+}
+
+#[snippet("Hirschberg")]
+fn hirschberg_rec<T: Eq>(s: &[T], t: &[T], i0: usize, j0: usize, cost: &EditCost, ops: &mut Vec<EditOp>) {
+    let n = s.len();
+    let m = t.len();
+    if n == 0 {
+        for j in 0..m {
+            ops.push(EditOp::Insert(j0+j));
+        }
+        return;
+    }
+    if n == 1 {
+        // s[0]をどこかに対応させるか、消す
+        let mut best = (cost.delete + m as i64 * cost.insert, m);
+        for j in 0..m {
+            let c = if s[0] == t[j] { 0 } else { cost.substitute };
+            let c = c + (m as i64 - 1) * cost.insert;
+            if c < best.0 {
+                best = (c, j);
+            }
+        }
+        let k = best.1;
+        for j in 0..m {
+            if j == k {
+                ops.push(if s[0] == t[j] { EditOp::Keep(i0, j0+j) } else { EditOp::Substitute(i0, j0+j) });
+            } else {
+                ops.push(EditOp::Insert(j0+j));
+            }
+        }
+        if k == m {
+            ops.push(EditOp::Delete(i0));
+        }
+        return;
+    }
+    let mid = n/2;
+    let f = hirschberg_last_row(&s[..mid], t, cost, false);
+    let g = hirschberg_last_row(&s[mid..], t, cost, true);
+    let mut k = 0;
+    for j in 0..m+1 {
+        if f[j] + g[m-j] < f[k] + g[m-k] {
+            k = j;
+        }
+    }
+    hirschberg_rec(&s[..mid], &t[..k], i0, j0, cost, ops);
+    hirschberg_rec(&s[mid..], &t[k..], i0+mid, j0+k, cost, ops);
+}
+
+#[cfg(test)]
+fn weighted_edit_distance<T: Eq>(s: &[T], t: &[T], cost: &EditCost) -> i64 {
+    let (n, m) = (s.len(), t.len());
+    let mut dp = vec![vec![0; m+1]; n+1];
+    for i in 0..n+1 {
+        for j in 0..m+1 {
+            dp[i][j] = if i == 0 {
+                j as i64 * cost.insert
+            } else if j == 0 {
+                i as i64 * cost.delete
+            } else {
+                let c = if s[i-1] == t[j-1] { 0 } else { cost.substitute };
+                min(dp[i-1][j-1] + c, min(dp[i-1][j] + cost.delete, dp[i][j-1] + cost.insert))
+            };
+        }
+    }
+    dp[n][m]
+}
+
+#[cfg(test)]
+fn check_alignment<T: Eq + Clone + std::fmt::Debug>(s: &[T], t: &[T], ops: &[EditOp]) {
+    // 操作列がsの各文字とtの各文字をちょうど一回ずつ順番に使っていること
+    let (mut i, mut j) = (0, 0);
+    for op in ops {
+        match *op {
+            EditOp::Keep(a, b) => {
+                assert_eq!((a, b), (i, j));
+                assert_eq!(s[a], t[b]);
+                i += 1;
+                j += 1;
+            },
+            EditOp::Substitute(a, b) => {
+                assert_eq!((a, b), (i, j));
+                i += 1;
+                j += 1;
+            },
+            EditOp::Delete(a) => {
+                assert_eq!(a, i);
+                i += 1;
+            },
+            EditOp::Insert(b) => {
+                assert_eq!(b, j);
+                j += 1;
+            },
+        }
+    }
+    assert_eq!((i, j), (s.len(), t.len()));
+}
+
+#[test]
+fn test_edit_alignment() {
+    let s = vec!['a','c','a','c'];
+    let t = vec!['a','c','m'];
+    let unit = EditCost { insert: 1, delete: 1, substitute: 1 };
+    let (c, ops) = edit_alignment(&s, &t, &unit);
+    assert_eq!(c, 2);
+    check_alignment(&s, &t, &ops);
+
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..300 {
+        let n = rand.rand(15) as usize;
+        let m = rand.rand(15) as usize;
+        let s: Vec<u64> = (0..n).map(|_| rand.rand(3)).collect();
+        let t: Vec<u64> = (0..m).map(|_| rand.rand(3)).collect();
+        let cost = EditCost {
+            insert: 1 + rand.rand(5) as i64,
+            delete: 1 + rand.rand(5) as i64,
+            substitute: 1 + rand.rand(10) as i64,
+        };
+        let (c, ops) = edit_alignment(&s, &t, &cost);
+        assert_eq!(c, weighted_edit_distance(&s, &t, &cost));
+        check_alignment(&s, &t, &ops);
+        let (c, _) = edit_alignment(&s, &t, &unit);
+        assert_eq!(c as usize, edit_distance(s.clone(), t.clone())[n][m]);
+    }
+}
+
+#[test]
+fn test_edit_alignment_long() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    let s: Vec<u64> = (0..2000).map(|_| rand.rand(4)).collect();
+    let t: Vec<u64> = (0..3000).map(|_| rand.rand(4)).collect();
+    let unit = EditCost { insert: 1, delete: 1, substitute: 1 };
+    let (c, ops) = edit_alignment(&s, &t, &unit);
+    check_alignment(&s, &t, &ops);
+    assert_eq!(c as usize, edit_distance(s, t)[2000][3000]);
+}
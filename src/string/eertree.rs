@@ -0,0 +1,208 @@
+use cargo_snippet::snippet;
+use std::collections::BTreeMap;
+
+/// 回文木 (eertree)
+///
+/// 各ノードは異なる回文一つに対応する。
+/// ノード0は長さ-1の仮想的な根、ノード1は空文字列。
+/// 回文X -> cXc の遷移と、
+/// 最長の真のsuffix回文へのsuffix linkを持つ。
+/// 異なる回文は高々|S|個しかない。
+///
+/// series link:
+/// diff[v] = len[v] - len[link[v]] として、
+/// diffが変わるところまでlinkを飛ばしたもの。
+/// suffix回文の長さはO(log|S|)個の等差数列に分かれるので、
+/// 最小回文分割のDPが一文字あたりO(log|S|)で回る。
+///
+/// 計算量:
+/// push: O(log σ) amortized + O(log|S|)
+
+#[snippet("Eertree")]
+pub struct Eertree {
+    pub s: Vec<u64>,
+    pub len: Vec<i64>,
+    pub link: Vec<usize>,
+    pub next: Vec<BTreeMap<u64, usize>>,
+    /// その回文を最長のsuffix回文として終わる位置の数
+    cnt: Vec<usize>,
+    /// 最初に現れた時の終了位置(0-indexed)
+    pub first_end: Vec<usize>,
+    pub diff: Vec<i64>,
+    pub series_link: Vec<usize>,
+    series_ans: Vec<usize>,
+    /// dp[i] = s[..i]の最小回文分割数
+    pub dp: Vec<usize>,
+    /// sの最長のsuffix回文
+    pub last: usize,
+}
+#[snippet("Eertree")]
+impl Eertree {
+    pub fn new() -> Self {
+        Eertree {
+            s: vec![],
+            len: vec![-1, 0],
+            link: vec![0, 0],
+            next: vec![BTreeMap::new(), BTreeMap::new()],
+            cnt: vec![0, 0],
+            first_end: vec![0, 0],
+            diff: vec![0, 0],
+            series_link: vec![0, 0],
+            series_ans: vec![0, 0],
+            dp: vec![0],
+            last: 1,
+        }
+    }
+    pub fn from_slice(s: &[u64]) -> Self {
+        let mut t = Self::new();
+        for &c in s {
+            t.push(c);
+        }
+        t
+    }
+    pub fn size(&self) -> usize {
+        self.len.len()
+    }
+    /// vから始めてlinkを辿り、c X cの形に伸ばせる最初の回文X
+    fn find(&self, v: usize, c: u64) -> usize {
+        let i = self.s.len() - 1;
+        let mut v = v;
+        loop {
+            let l = self.len[v];
+            if i as i64 - l - 1 >= 0 && self.s[(i as i64 - l - 1) as usize] == c {
+                return v;
+            }
+            // 長さ-1の根では常に止まる
+            if v == 0 {
+                return 0;
+            }
+            v = self.link[v];
+        }
+    }
+    pub fn push(&mut self, c: u64) {
+        self.s.push(c);
+        let i = self.s.len() - 1;
+        let cur = self.find(self.last, c);
+        let v = match self.next[cur].get(&c) {
+            Some(&v) => v,
+            None => {
+                let len = self.len[cur] + 2;
+                let link = if len == 1 {
+                    1
+                } else {
+                    let w = self.find(self.link[cur], c);
+                    self.next[w][&c]
+                };
+                let diff = len - self.len[link];
+                let series_link = if diff == self.diff[link] { self.series_link[link] } else { link };
+                self.len.push(len);
+                self.link.push(link);
+                self.next.push(BTreeMap::new());
+                self.cnt.push(0);
+                self.first_end.push(i);
+                self.diff.push(diff);
+                self.series_link.push(series_link);
+                self.series_ans.push(0);
+                let v = self.size() - 1;
+                self.next[cur].insert(c, v);
+                v
+            },
+        };
+        self.last = v;
+        self.cnt[v] += 1;
+
+        // 最小回文分割
+        let n = self.s.len();
+        let mut best = std::usize::MAX;
+        let mut v = self.last;
+        while self.len[v] > 0 {
+            let sl = self.series_link[v];
+            let k = (self.len[sl] + self.diff[v]) as usize;
+            self.series_ans[v] = self.dp[n - k];
+            if self.diff[v] == self.diff[self.link[v]] {
+                let x = self.series_ans[self.link[v]];
+                self.series_ans[v] = std::cmp::min(self.series_ans[v], x);
+            }
+            best = std::cmp::min(best, self.series_ans[v] + 1);
+            v = sl;
+        }
+        self.dp.push(best);
+    }
+    /// 異なる回文(空文字列を除く)の数
+    pub fn distinct_palindromes(&self) -> usize {
+        self.size() - 2
+    }
+    /// 各ノードの回文がsに現れる回数
+    pub fn occurrence_counts(&self) -> Vec<usize> {
+        let mut cnt = self.cnt.clone();
+        // linkは必ず先に作られている
+        for v in (2..self.size()).rev() {
+            let l = self.link[v];
+            cnt[l] += cnt[v];
+        }
+        cnt
+    }
+    /// ノードvの回文
+    pub fn palindrome(&self, v: usize) -> &[u64] {
+        let e = self.first_end[v] + 1;
+        &self.s[e - self.len[v] as usize..e]
+    }
+    /// sを回文に分割するときの最小の個数
+    pub fn min_palindromic_factorization(&self) -> usize {
+        self.dp[self.s.len()]
+    }
+}
+
+#[test]
+fn test_eertree() {
+    let s: Vec<u64> = "eertree".chars().map(|c| c as u64).collect();
+    let t = Eertree::from_slice(&s);
+    // e, ee, r, t, rtr, ertre, eertree
+    assert_eq!(t.distinct_palindromes(), 7);
+    let cnt = t.occurrence_counts();
+    let e = t.next[0][&('e' as u64)];
+    assert_eq!(cnt[e], 4);
+    assert_eq!(t.palindrome(t.last), &s[..]);
+    assert_eq!(t.min_palindromic_factorization(), 1);
+    assert_eq!(t.dp, [0, 1, 1, 2, 3, 2, 2, 1]);
+}
+
+#[test]
+fn test_eertree_vs_manacher() {
+    use crate::string::manacher::Manacher;
+    use crate::xorshift::Xorshift;
+    use std::collections::HashMap;
+    let mut rand = Xorshift::new();
+    for _ in 0..200 {
+        let n = rand.rand(40) as usize;
+        let sigma = 1 + rand.rand(3);
+        let s: Vec<u64> = (0..n).map(|_| rand.rand(sigma)).collect();
+        let t = Eertree::from_slice(&s);
+        let m = Manacher::new(s.clone());
+
+        let mut expected: HashMap<&[u64], usize> = HashMap::new();
+        for l in 0..n {
+            for r in l+1..n+1 {
+                if m.is_pelimdrome(l, r) {
+                    *expected.entry(&s[l..r]).or_insert(0) += 1;
+                }
+            }
+        }
+        assert_eq!(t.distinct_palindromes(), expected.len());
+        let cnt = t.occurrence_counts();
+        for v in 2..t.size() {
+            assert_eq!(cnt[v], expected[t.palindrome(v)]);
+        }
+
+        let mut dp = vec![std::usize::MAX; n+1];
+        dp[0] = 0;
+        for r in 1..n+1 {
+            for l in 0..r {
+                if m.is_pelimdrome(l, r) {
+                    dp[r] = std::cmp::min(dp[r], dp[l] + 1);
+                }
+            }
+        }
+        assert_eq!(t.dp, dp);
+    }
+}
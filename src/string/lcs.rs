@@ -76,4 +76,95 @@ fn test_lcs1() {
     let lcs = LCS::new(&xs, &ys);
     assert_eq!(lcs.matching.len(), 7);
     assert_eq!(lcs.matching, vec![(0,0),(1,1),(2,3),(3,4),(4,5),(5,6),(6,8)]);
-}
\ No newline at end of file
+}
+/// LCSの復元をメモリO(N+M)で行う。
+/// 挿入と削除のコストを1、置換のコストを2とした編集距離は
+/// N+M-2*LCS になるので、Hirschbergで求めたKeepがLCSになる。
+///
+/// 計算量 O(NM)
+/// 空間量 O(N+M)
+
+#[snippet("Hirschberg")]
+pub fn lcs_matching_hirschberg<T: Eq>(xs: &[T], ys: &[T]) -> Vec<(usize,usize)> {
+    use crate::string::edit_distance::{edit_alignment, EditCost, EditOp};
+    let cost = EditCost { insert: 1, delete: 1, substitute: 2 };
+    let (_, ops) = edit_alignment(xs, ys, &cost);
+    ops.into_iter().filter_map(|op| match op {
+        EditOp::Keep(i, j) => Some((i, j)),
+        _ => None,
+    }).collect()
+}
+
+/// ビット並列によるLCSの長さ
+///
+/// xsの各位置をビットにして、ysを一文字ずつ読むたびに
+/// V = (V + (V & M)) | (V & !M)
+/// で更新する(Mはその文字が現れるxsの位置)。
+/// LCSの長さはVの中の0の数になる。
+///
+/// 計算量 O(NM/64)
+
+#[snippet("lcs_bitparallel")]
+pub fn lcs_length_bitparallel(xs: &[u64], ys: &[u64]) -> usize {
+    let n = xs.len();
+    let w = (n + 63) / 64;
+    let mut masks: std::collections::HashMap<u64, Vec<u64>> = std::collections::HashMap::new();
+    for (i, &x) in xs.iter().enumerate() {
+        masks.entry(x).or_insert(vec![0; w])[i/64] |= 1 << (i%64);
+    }
+    let mut v = vec![!0u64; w];
+    for y in ys {
+        let m = match masks.get(y) {
+            Some(m) => m,
+            None => continue,
+        };
+        let mut carry = 0;
+        for k in 0..w {
+            let u = v[k] & m[k];
+            let (s1, c1) = v[k].overflowing_add(u);
+            let (s2, c2) = s1.overflowing_add(carry);
+            carry = (c1 || c2) as u64;
+            v[k] = s2 | (v[k] & !m[k]);
+        }
+    }
+    let mut zeros = 0;
+    for k in 0..w {
+        let bits = std::cmp::min(64, n - 64*k);
+        let mask = if bits == 64 { !0 } else { (1u64 << bits) - 1 };
+        zeros += (!v[k] & mask).count_ones() as usize;
+    }
+    zeros
+}
+
+#[test]
+fn test_lcs_hirschberg_and_bitparallel() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..300 {
+        let n = rand.rand(150) as usize;
+        let m = rand.rand(150) as usize;
+        let sigma = 1 + rand.rand(4);
+        // 大きな値でも動くように
+        let xs: Vec<u64> = (0..n).map(|_| rand.rand(sigma) << 40).collect();
+        let ys: Vec<u64> = (0..m).map(|_| rand.rand(sigma) << 40).collect();
+        let k = LCS::new(&xs, &ys).matching.len();
+        let matching = lcs_matching_hirschberg(&xs, &ys);
+        assert_eq!(matching.len(), k);
+        for w in matching.windows(2) {
+            assert!(w[0].0 < w[1].0 && w[0].1 < w[1].1);
+        }
+        for &(i, j) in &matching {
+            assert_eq!(xs[i], ys[j]);
+        }
+        assert_eq!(lcs_length_bitparallel(&xs, &ys), k);
+    }
+}
+
+#[bench]
+fn bench_lcs_bitparallel(b: &mut test::Bencher) {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    let xs: Vec<u64> = (0..30000).map(|_| rand.rand(26)).collect();
+    let ys: Vec<u64> = (0..30000).map(|_| rand.rand(26)).collect();
+    b.iter(|| lcs_length_bitparallel(&xs, &ys))
+}
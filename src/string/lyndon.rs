@@ -0,0 +1,233 @@
+use cargo_snippet::snippet;
+use crate::string::suffix_array::SuffixArray;
+use crate::sparse_table::SparseTable;
+
+/// Lyndon分解 (Duval)
+///
+/// 文字列を、辞書順に広義単調減少するLyndon文字列の列に一意に分解する。
+/// Lyndon文字列とは、自分のどの真のsuffixよりも真に小さい文字列。
+/// 分解を[l,r)の列で返す。
+///
+/// 計算量 O(N)
+
+#[snippet("lyndon_factorization")]
+pub fn lyndon_factorization(s: &[u64]) -> Vec<(usize, usize)> {
+    let n = s.len();
+    let mut res = vec![];
+    let mut i = 0;
+    while i < n {
+        let mut j = i+1;
+        let mut k = i;
+        while j < n && s[k] <= s[j] {
+            if s[k] < s[j] {
+                k = i;
+            } else {
+                k += 1;
+            }
+            j += 1;
+        }
+        // s[i..j]は長さj-kのLyndon文字列の繰り返し(+そのprefix)
+        while i <= k {
+            res.push((i, i+j-k));
+            i += j-k;
+        }
+    }
+    res
+}
+
+/// 辞書順最小の回転 (Booth)
+///
+/// s[k..] + s[..k] が最小となるkのうち最小のもの。
+/// s+sの上でKMPの失敗関数を作りながら、候補の開始位置kを更新していく。
+///
+/// 計算量 O(N)
+
+#[snippet("min_rotation")]
+pub fn min_rotation(s: &[u64]) -> usize {
+    let n = s.len();
+    if n == 0 {
+        return 0;
+    }
+    let mut f: Vec<i64> = vec![-1; 2*n];
+    let mut k = 0;
+    for j in 1..2*n {
+        let sj = s[j%n];
+        let mut i = f[j-k-1];
+        while i != -1 && sj != s[(k + i as usize + 1) % n] {
+            if sj < s[(k + i as usize + 1) % n] {
+                k = j - i as usize - 1;
+            }
+            i = f[i as usize];
+        }
+        if i == -1 && sj != s[k%n] {
+            if sj < s[k%n] {
+                k = j;
+            }
+            f[j-k] = -1;
+        } else {
+            f[j-k] = i + 1;
+        }
+    }
+    // 周期的な文字列ではkが周期の倍数だけずれることがある
+    k % n
+}
+
+/// 接尾辞同士の最長共通接頭辞をO(1)で求める
+#[snippet("runs")]
+struct SuffixLcp {
+    n: usize,
+    rank: Vec<usize>,
+    lcp: Vec<usize>,
+    st: SparseTable,
+}
+#[snippet("runs")]
+impl SuffixLcp {
+    fn new(s: &[u64]) -> SuffixLcp {
+        let n = s.len();
+        let sa = SuffixArray::new(s.to_vec());
+        let mut rank = vec![0; n];
+        for i in 0..n {
+            rank[sa.sa[i]] = i;
+        }
+        let st = SparseTable::new(sa.lcp.iter().map(|&x| x as i64).collect());
+        SuffixLcp {
+            n: n,
+            rank: rank,
+            lcp: sa.lcp,
+            st: st,
+        }
+    }
+    /// s[i..]とs[j..]のLCP
+    fn get(&self, i: usize, j: usize) -> usize {
+        if i == self.n || j == self.n {
+            return 0;
+        }
+        if i == j {
+            return self.n - i;
+        }
+        let (a, b) = if self.rank[i] < self.rank[j] { (self.rank[i], self.rank[j]) } else { (self.rank[j], self.rank[i]) };
+        self.lcp[self.st.query(a, b)]
+    }
+}
+
+/// 連 (run) の列挙
+///
+/// 連とは、最小周期pの繰り返しが2回以上続く極大な区間[l,r)のこと。
+/// 各連は、文字の順序を正順か逆順のどちらかにしたとき、
+/// 長さpのLyndon文字列(その位置から始まる最長のLyndon文字列)を含む。
+/// そこで両方の順序でLyndon配列を作り、
+/// 各候補を前後にLCPで伸ばして連になるものを集める。
+/// 連の数はN未満であることが知られている。
+///
+/// (l, r, p)をlの昇順で返す。
+///
+/// 計算量 O(N logN)
+
+#[snippet("runs")]
+pub fn runs(s: &[u64]) -> Vec<(usize, usize, usize)> {
+    let n = s.len();
+    if n == 0 {
+        return vec![];
+    }
+    let rev: Vec<u64> = s.iter().rev().cloned().collect();
+    let fwd = SuffixLcp::new(s);
+    let bwd = SuffixLcp::new(&rev);
+    let mut res = vec![];
+    for &inv in &[false, true] {
+        let t: Vec<u64> = if inv { s.iter().map(|&x| !x).collect() } else { s.to_vec() };
+        let sa = SuffixArray::new(t);
+        let mut rank = vec![0; n+1];
+        for i in 0..n {
+            rank[sa.sa[i]] = i+1;
+        }
+        // 最長のLyndon文字列は、次に自分より小さいsuffixが始まる手前で終わる
+        let mut st: Vec<usize> = vec![n];
+        for i in (0..n).rev() {
+            while rank[*st.last().unwrap()] > rank[i] {
+                st.pop();
+            }
+            let j = *st.last().unwrap();
+            st.push(i);
+            let p = j - i;
+            let l = i - if i == 0 { 0 } else { bwd.get(n-i, n-j) };
+            let r = j + fwd.get(i, j);
+            if r - l >= 2*p {
+                res.push((l, r, p));
+            }
+        }
+    }
+    res.sort();
+    res.dedup_by(|a, b| (a.0, a.1) == (b.0, b.1));
+    res
+}
+
+#[test]
+fn test_lyndon_factorization() {
+    let s: Vec<u64> = "abbabaabaa".chars().map(|c| c as u64).collect();
+    assert_eq!(lyndon_factorization(&s), [(0, 3), (3, 5), (5, 8), (8, 9), (9, 10)]);
+
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..500 {
+        let n = rand.rand(20) as usize;
+        let s: Vec<u64> = (0..n).map(|_| rand.rand(3)).collect();
+        let f = lyndon_factorization(&s);
+        let is_lyndon = |w: &[u64]| (1..w.len()).all(|k| w < &w[k..]);
+        let mut i = 0;
+        for (k, &(l, r)) in f.iter().enumerate() {
+            assert_eq!(l, i);
+            assert!(is_lyndon(&s[l..r]));
+            if k > 0 {
+                let (pl, pr) = f[k-1];
+                assert!(s[pl..pr] >= s[l..r]);
+            }
+            i = r;
+        }
+        assert_eq!(i, n);
+    }
+}
+
+#[test]
+fn test_min_rotation() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..1000 {
+        let n = 1 + rand.rand(20) as usize;
+        let sigma = 1 + rand.rand(3);
+        let s: Vec<u64> = (0..n).map(|_| rand.rand(sigma)).collect();
+        let rot = |k: usize| [&s[k..], &s[..k]].concat();
+        let best = (0..n).min_by(|&a, &b| rot(a).cmp(&rot(b)).then(a.cmp(&b))).unwrap();
+        assert_eq!(min_rotation(&s), best);
+    }
+}
+
+#[test]
+fn test_runs() {
+    let s: Vec<u64> = "aabaabaa".chars().map(|c| c as u64).collect();
+    assert_eq!(runs(&s), [(0, 2, 1), (0, 8, 3), (3, 5, 1), (6, 8, 1)]);
+
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..500 {
+        let n = rand.rand(25) as usize;
+        let sigma = 1 + rand.rand(3);
+        let s: Vec<u64> = (0..n).map(|_| rand.rand(sigma)).collect();
+        let mut expected = vec![];
+        for l in 0..n {
+            for r in l+1..n+1 {
+                // 最小周期
+                let p = (1..r-l+1).find(|&p| (l+p..r).all(|i| s[i] == s[i-p])).unwrap();
+                if r - l < 2*p {
+                    continue;
+                }
+                // 極大性
+                let left = l > 0 && s[l-1] == s[l-1+p];
+                let right = r < n && s[r] == s[r-p];
+                if !left && !right {
+                    expected.push((l, r, p));
+                }
+            }
+        }
+        assert_eq!(runs(&s), expected);
+    }
+}
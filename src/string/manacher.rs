@@ -37,7 +37,7 @@ fn test_manacher_raw() {
 }
 
 #[snippet("Manacher")]
-struct Manacher {
+pub struct Manacher {
     a: Vec<usize>,
 }
 #[snippet("Manacher")]
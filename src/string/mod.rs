@@ -3,6 +3,10 @@ pub mod manacher;
 pub mod roliha;
 pub mod z_algorithm;
 pub mod suffix_array;
+pub mod suffix_automaton;
+pub mod aho_corasick;
+pub mod eertree;
+pub mod lyndon;
 pub mod compare;
 pub mod lcs;
 pub mod edit_distance;
\ No newline at end of file
@@ -3,27 +3,69 @@
 use cargo_snippet::snippet;
 
 use crate::xorshift::Xorshift;
+use crate::fenwick::BIT;
+/// ローリングハッシュ
+///
+/// 法は2^61-1で、基数は実行ごとに変わる乱数で決める。
+/// 文字xは内部でx+1として扱うので、0を含んでもよい(x < 2^61-2)。
+///
+/// 同じ基数で作ったRoLiHa同士なら、別の文字列のハッシュ値も比較できる。
+/// その場合はwith_baseで基数を揃える。
+///
+/// 計算量:
+/// 構築: O(N)
+/// get: O(1)
+/// lcp, compare: O(logN)
+
 #[snippet("RoLiHa")]
-struct RoLiHa {
+#[snippet("RoLiHa2D")]
+#[snippet("DynamicRoLiHa")]
+pub struct RoLiHa {
+    base: u64,
     powMemo: Vec<u64>,
     hash: Vec<u64>,
+    s: Vec<u64>,
 }
 #[snippet("RoLiHa")]
+#[snippet("RoLiHa2D")]
+#[snippet("DynamicRoLiHa")]
 const ROLIHA_MASK30: u64 = (1<<30) - 1;
 #[snippet("RoLiHa")]
+#[snippet("RoLiHa2D")]
+#[snippet("DynamicRoLiHa")]
 const ROLIHA_MASK31: u64 = (1<<31) - 1;
 #[snippet("RoLiHa")]
+#[snippet("RoLiHa2D")]
+#[snippet("DynamicRoLiHa")]
 const ROLIHA_MOD: u64 = (1<<61) - 1;
 #[snippet("RoLiHa")]
+#[snippet("RoLiHa2D")]
+#[snippet("DynamicRoLiHa")]
 const ROLIHA_P: u64 = ROLIHA_MOD * ((1<<3) - 1);
 #[snippet("RoLiHa")]
+#[snippet("RoLiHa2D")]
+#[snippet("DynamicRoLiHa")]
 impl RoLiHa {
-    #[doc = "注意: Sに0は含まれてはいけない"]
-    fn new(s: &[u64]) -> Self {
-        let mut randgen = Xorshift::new();
-        let rand = randgen.rand(std::i64::MAX as u64);
-        let base = rand+129;
+    pub fn new(s: &[u64]) -> Self {
+        Self::with_base(s, Self::random_base())
+    }
+
+    /// 実行ごとに異なる基数。時刻とRandomStateから種を作るので、
+    /// 基数を狙い撃ちした入力で衝突させられることはない
+    fn random_base() -> u64 {
+        use std::hash::{BuildHasher, Hasher};
+        let mut h = std::collections::hash_map::RandomState::new().build_hasher();
+        let t = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        h.write_u64(t);
+        let mut randgen = Xorshift::with_seed(h.finish() | 1);
+        randgen.rand(ROLIHA_MOD - 129) + 129
+    }
 
+    #[doc = "基数を指定して作る。2 <= base < 2^61-1"]
+    pub fn with_base(s: &[u64], base: u64) -> Self {
         let mut powMemo = vec![0; s.len()+1];
         powMemo[0]=1;
         for i in 1..powMemo.len() {
@@ -32,15 +74,25 @@ impl RoLiHa {
 
         let mut hash = vec![0; s.len()+1];
         for i in 0..s.len() {
-            hash[i+1] = Self::calcmod(Self::mul(hash[i], base) + s[i]);
+            hash[i+1] = Self::calcmod(Self::mul(hash[i], base) + Self::symbol(s[i]));
         }
 
         RoLiHa {
+            base: base,
             powMemo: powMemo,
             hash: hash,
+            s: s.to_vec(),
         }
     }
 
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    pub fn len(&self) -> usize {
+        self.s.len()
+    }
+
     /// [l,r)のハッシュ値を計算する
     pub fn get(&self, l: usize, r: usize) -> u64 {
         return Self::calcmod(self.hash[r] + ROLIHA_P - Self::mul(self.hash[l], self.powMemo[r-l]));
@@ -50,6 +102,46 @@ impl RoLiHa {
         return Self::calcmod(Self::mul(h1, self.powMemo[h2len]) + h2)
     }
 
+    /// self[l1,r1)とother[l2,r2)の最長共通接頭辞の長さ。
+    /// otherは同じ基数であること
+    pub fn lcp_with(&self, l1: usize, r1: usize, other: &RoLiHa, l2: usize, r2: usize) -> usize {
+        let mut ok = 0;
+        let mut ng = std::cmp::min(r1-l1, r2-l2) + 1;
+        while ng - ok > 1 {
+            let mid = (ok+ng)/2;
+            if self.get(l1, l1+mid) == other.get(l2, l2+mid) {
+                ok = mid;
+            } else {
+                ng = mid;
+            }
+        }
+        ok
+    }
+
+    /// [l1,r1)と[l2,r2)の最長共通接頭辞の長さ
+    pub fn lcp(&self, l1: usize, r1: usize, l2: usize, r2: usize) -> usize {
+        self.lcp_with(l1, r1, self, l2, r2)
+    }
+
+    /// self[l1,r1)とother[l2,r2)の辞書順比較
+    pub fn compare_with(&self, l1: usize, r1: usize, other: &RoLiHa, l2: usize, r2: usize) -> std::cmp::Ordering {
+        let k = self.lcp_with(l1, r1, other, l2, r2);
+        if l1+k == r1 || l2+k == r2 {
+            (r1-l1).cmp(&(r2-l2))
+        } else {
+            self.s[l1+k].cmp(&other.s[l2+k])
+        }
+    }
+
+    /// [l1,r1)と[l2,r2)の辞書順比較
+    pub fn compare(&self, l1: usize, r1: usize, l2: usize, r2: usize) -> std::cmp::Ordering {
+        self.compare_with(l1, r1, self, l2, r2)
+    }
+
+    fn symbol(x: u64) -> u64 {
+        Self::calcmod(x) + 1
+    }
+
     fn mul(l: u64, r: u64) -> u64 {
         let lu = l >> 31;
         let ld = l & ROLIHA_MASK31;
@@ -61,11 +153,173 @@ impl RoLiHa {
 
     fn calcmod(x: u64) -> u64 {
         let mut x = (x & ROLIHA_MOD) + (x>>61);
-        if x > ROLIHA_MOD {
+        if x >= ROLIHA_MOD {
             x -= ROLIHA_MOD;
         }
         x
     }
+
+    fn pow(a: u64, n: u64) -> u64 {
+        let mut res = 1;
+        let mut a = a;
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 {
+                res = Self::calcmod(Self::mul(res, a));
+            }
+            a = Self::calcmod(Self::mul(a, a));
+            n >>= 1;
+        }
+        res
+    }
+}
+
+/// 二次元ローリングハッシュ
+///
+/// 行方向と列方向で別の基数を使う。
+/// 同じ基数で作ったもの同士なら、部分行列のハッシュ値を比較できる。
+///
+/// 計算量:
+/// 構築: O(HW)
+/// get: O(1)
+
+#[snippet("RoLiHa2D")]
+pub struct RoLiHa2D {
+    pub base: (u64, u64),
+    pow_r: Vec<u64>,
+    pow_c: Vec<u64>,
+    hash: Vec<Vec<u64>>,
+}
+#[snippet("RoLiHa2D")]
+impl RoLiHa2D {
+    pub fn new(a: &[Vec<u64>]) -> Self {
+        let br = RoLiHa::random_base();
+        let mut randgen = Xorshift::with_seed(br);
+        let bc = randgen.rand(ROLIHA_MOD - 129) + 129;
+        Self::with_base(a, (br, bc))
+    }
+
+    pub fn with_base(a: &[Vec<u64>], base: (u64, u64)) -> Self {
+        let h = a.len();
+        let w = if h == 0 { 0 } else { a[0].len() };
+        let pows = |b: u64, n: usize| {
+            let mut v = vec![1; n+1];
+            for i in 1..n+1 {
+                v[i] = RoLiHa::calcmod(RoLiHa::mul(v[i-1], b));
+            }
+            v
+        };
+        let mut hash = vec![vec![0; w+1]; h+1];
+        for i in 0..h {
+            // 行ごとのハッシュを列方向に積み上げる
+            let mut row = 0;
+            for j in 0..w {
+                row = RoLiHa::calcmod(RoLiHa::mul(row, base.1) + RoLiHa::symbol(a[i][j]));
+                hash[i+1][j+1] = RoLiHa::calcmod(RoLiHa::mul(hash[i][j+1], base.0) + row);
+            }
+        }
+        RoLiHa2D {
+            base: base,
+            pow_r: pows(base.0, h),
+            pow_c: pows(base.1, w),
+            hash: hash,
+        }
+    }
+
+    fn sub(a: u64, b: u64) -> u64 {
+        RoLiHa::calcmod(a + ROLIHA_MOD - b)
+    }
+
+    /// [r1,r2)x[c1,c2)のハッシュ値
+    pub fn get(&self, r1: usize, c1: usize, r2: usize, c2: usize) -> u64 {
+        let pr = self.pow_r[r2-r1];
+        let pc = self.pow_c[c2-c1];
+        let x = Self::sub(self.hash[r2][c2], RoLiHa::calcmod(RoLiHa::mul(self.hash[r1][c2], pr)));
+        let y = Self::sub(self.hash[r2][c1], RoLiHa::calcmod(RoLiHa::mul(self.hash[r1][c1], pr)));
+        Self::sub(x, RoLiHa::calcmod(RoLiHa::mul(y, pc)))
+    }
+}
+
+/// BITの要素にするための2^61-1での加算
+#[snippet("DynamicRoLiHa")]
+#[derive(Clone, Copy, Default)]
+pub struct RoLiHaValue(u64);
+#[snippet("DynamicRoLiHa")]
+impl std::ops::AddAssign for RoLiHaValue {
+    fn add_assign(&mut self, x: RoLiHaValue) {
+        self.0 = RoLiHa::calcmod(self.0 + x.0);
+    }
+}
+
+/// 一点更新できるローリングハッシュ
+///
+/// s[i] * base^{-i} をBITに載せておき、
+/// 区間和にbase^{r-1}を掛けるとRoLiHa::getと同じ値になる。
+///
+/// 計算量:
+/// set, get: O(logN)
+
+#[snippet("DynamicRoLiHa")]
+pub struct DynamicRoLiHa {
+    base: u64,
+    pow_memo: Vec<u64>,
+    ipow_memo: Vec<u64>,
+    bit: BIT<RoLiHaValue>,
+    s: Vec<u64>,
+}
+#[snippet("DynamicRoLiHa")]
+impl DynamicRoLiHa {
+    pub fn new(s: &[u64]) -> Self {
+        Self::with_base(s, RoLiHa::random_base())
+    }
+
+    pub fn with_base(s: &[u64], base: u64) -> Self {
+        let n = s.len();
+        let ibase = RoLiHa::pow(base, ROLIHA_MOD - 2);
+        let mut pow_memo = vec![1; n+1];
+        let mut ipow_memo = vec![1; n+1];
+        for i in 1..n+1 {
+            pow_memo[i] = RoLiHa::calcmod(RoLiHa::mul(pow_memo[i-1], base));
+            ipow_memo[i] = RoLiHa::calcmod(RoLiHa::mul(ipow_memo[i-1], ibase));
+        }
+        let mut res = DynamicRoLiHa {
+            base: base,
+            pow_memo: pow_memo,
+            ipow_memo: ipow_memo,
+            bit: BIT::new(n),
+            s: vec![0; n],
+        };
+        for i in 0..n {
+            res.add_symbol(i, RoLiHa::symbol(s[i]));
+        }
+        res.s = s.to_vec();
+        res
+    }
+
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    fn add_symbol(&mut self, i: usize, x: u64) {
+        let v = RoLiHa::calcmod(RoLiHa::mul(x, self.ipow_memo[i]));
+        self.bit.add(i+1, RoLiHaValue(v));
+    }
+
+    /// s[i] = x
+    pub fn set(&mut self, i: usize, x: u64) {
+        let d = RoLiHa::calcmod(RoLiHa::symbol(x) + ROLIHA_MOD - RoLiHa::symbol(self.s[i]));
+        self.add_symbol(i, d);
+        self.s[i] = x;
+    }
+
+    /// [l,r)のハッシュ値を計算する
+    pub fn get(&self, l: usize, r: usize) -> u64 {
+        if l == r {
+            return 0;
+        }
+        let x = RoLiHa::calcmod(self.bit.sum(r).0 + ROLIHA_MOD - self.bit.sum(l).0);
+        RoLiHa::calcmod(RoLiHa::mul(x, self.pow_memo[r-1]))
+    }
 }
 
 #[test]
@@ -77,6 +331,13 @@ fn test_roliha_get() {
     assert_ne!(rh.get(0, 3), rh.get(2, 5));
 }
 
+#[test]
+fn test_roliha_random_base() {
+    let bases: Vec<u64> = (0..10).map(|_| RoLiHa::random_base()).collect();
+    assert!(bases.iter().all(|&b| 129 <= b && b < ROLIHA_MOD));
+    assert!(bases.windows(2).any(|w| w[0] != w[1]));
+}
+
 #[test]
 fn test_roliha_connect() {
     let mut rng = Xorshift::new();
@@ -99,6 +360,86 @@ fn test_roliha_connect() {
     }
 }
 
+#[test]
+fn test_roliha_zero() {
+    let rh = RoLiHa::new(&[0, 0, 1, 0, 1]);
+    assert_ne!(rh.get(0, 3), rh.get(1, 3));
+    assert_eq!(rh.get(1, 3), rh.get(3, 5));
+}
+
+#[test]
+fn test_roliha_lcp_compare() {
+    let mut rng = Xorshift::new();
+    let n = 60;
+    let s: Vec<u64> = (0..n).map(|_| rng.rand(2)).collect();
+    let rh = RoLiHa::new(&s);
+    for _ in 0..3000 {
+        let l1 = rng.rand(n as u64 + 1) as usize;
+        let r1 = l1 + rng.rand((n - l1) as u64 + 1) as usize;
+        let l2 = rng.rand(n as u64 + 1) as usize;
+        let r2 = l2 + rng.rand((n - l2) as u64 + 1) as usize;
+        let (a, b) = (&s[l1..r1], &s[l2..r2]);
+        let k = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+        assert_eq!(rh.lcp(l1, r1, l2, r2), k);
+        assert_eq!(rh.compare(l1, r1, l2, r2), a.cmp(b));
+    }
+}
+
+#[test]
+fn test_roliha_two_strings() {
+    let s: Vec<u64> = "xabcab".chars().map(|c| c as u64).collect();
+    let t: Vec<u64> = "abcabd".chars().map(|c| c as u64).collect();
+    let rs = RoLiHa::new(&s);
+    let rt = RoLiHa::with_base(&t, rs.base());
+    assert_eq!(rs.get(1, 6), rt.get(0, 5));
+    assert_ne!(rs.get(1, 6), rt.get(1, 6));
+    assert_eq!(rs.lcp_with(1, 6, &rt, 0, 6), 5);
+    assert_eq!(rs.compare_with(1, 6, &rt, 0, 6), std::cmp::Ordering::Less);
+    assert_eq!(rs.compare_with(0, 6, &rt, 0, 6), std::cmp::Ordering::Greater);
+}
+
+#[test]
+fn test_roliha_2d() {
+    let mut rng = Xorshift::new();
+    let (h, w) = (20, 25);
+    let a: Vec<Vec<u64>> = (0..h).map(|_| (0..w).map(|_| rng.rand(2)).collect()).collect();
+    let (ph, pw) = (2, 3);
+    let p: Vec<Vec<u64>> = (0..ph).map(|i| a[7+i][5..5+pw].to_vec()).collect();
+    let ra = RoLiHa2D::new(&a);
+    let rp = RoLiHa2D::with_base(&p, ra.base);
+    let target = rp.get(0, 0, ph, pw);
+    for i in 0..h-ph+1 {
+        for j in 0..w-pw+1 {
+            let naive = (0..ph).all(|di| a[i+di][j..j+pw] == p[di][..]);
+            assert_eq!(ra.get(i, j, i+ph, j+pw) == target, naive);
+        }
+    }
+    // 転置した形とは区別する
+    let a = vec![vec![1, 2], vec![3, 4]];
+    let b = vec![vec![1, 3], vec![2, 4]];
+    let ra = RoLiHa2D::new(&a);
+    let rb = RoLiHa2D::with_base(&b, ra.base);
+    assert_ne!(ra.get(0, 0, 2, 2), rb.get(0, 0, 2, 2));
+}
+
+#[test]
+fn test_dynamic_roliha() {
+    let mut rng = Xorshift::new();
+    let n = 50;
+    let mut s: Vec<u64> = (0..n).map(|_| rng.rand(3)).collect();
+    let mut drh = DynamicRoLiHa::new(&s);
+    for _ in 0..300 {
+        let i = rng.rand(n as u64) as usize;
+        let x = rng.rand(3);
+        s[i] = x;
+        drh.set(i, x);
+        let rh = RoLiHa::with_base(&s, drh.base());
+        let l = rng.rand(n as u64 + 1) as usize;
+        let r = l + rng.rand((n - l) as u64 + 1) as usize;
+        assert_eq!(drh.get(l, r), rh.get(l, r));
+    }
+}
+
 #[bench]
 fn bench_roliha(b: &mut test::Bencher) {
     let s = mk_str(1_000_000);
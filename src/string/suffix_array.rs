@@ -4,7 +4,8 @@ use cargo_snippet::snippet;
 /// suffix -> 順番
 /// を構築する。
 /// 
-/// 構築計算量 O(|S| log |S|)
+/// 構築はSA-ISで O(|S|)
+/// 文字はu64の任意の値でよい(内部で座標圧縮する)。
 /// 
 /// ある文字列Tがどれかのsuffixとprefixが一致するとき、
 /// マッチングが検索出来ていることになる。
@@ -12,93 +13,191 @@ use cargo_snippet::snippet;
 /// マッチングしたインデックスを調べることが出来る。
 /// 
 /// 計算量 O(|T| log |S|)
+///
+/// LCP配列はKasaiの方法で O(|S|)
+/// lcp[i] = 辞書順i番目とi+1番目のsuffixの最長共通接頭辞の長さ
 
 
 #[snippet("SuffixArray")]
-struct SuffixArray {
+const SA_NONE: usize = std::usize::MAX;
+#[snippet("SuffixArray")]
+fn sa_induce(lms: &[usize], s: &[usize], ls: &[bool], sum_s: &[usize], sum_l: &[usize], sa: &mut [usize]) {
+    let n = s.len();
+    for x in sa.iter_mut() {
+        *x = SA_NONE;
+    }
+    let mut buf = sum_s.to_vec();
+    for &d in lms {
+        if d == n {
+            continue;
+        }
+        sa[buf[s[d]]] = d;
+        buf[s[d]] += 1;
+    }
+    let mut buf = sum_l.to_vec();
+    sa[buf[s[n-1]]] = n-1;
+    buf[s[n-1]] += 1;
+    for i in 0..n {
+        let v = sa[i];
+        if v != SA_NONE && v >= 1 && !ls[v-1] {
+            sa[buf[s[v-1]]] = v-1;
+            buf[s[v-1]] += 1;
+        }
+    }
+    let mut buf = sum_l.to_vec();
+    for i in (0..n).rev() {
+        let v = sa[i];
+        if v != SA_NONE && v >= 1 && ls[v-1] {
+            buf[s[v-1]+1] -= 1;
+            sa[buf[s[v-1]+1]] = v-1;
+        }
+    }
+}
+#[snippet("SuffixArray")]
+#[doc = "SA-IS. s[i] <= upper"]
+fn sa_is(s: &[usize], upper: usize) -> Vec<usize> {
+    let n = s.len();
+    match n {
+        0 => return vec![],
+        1 => return vec![0],
+        2 => return if s[0] < s[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => {},
+    }
+    // ls[i]: suffix iがsuffix i+1より小さい(S型)
+    let mut ls = vec![false; n];
+    for i in (0..n-1).rev() {
+        ls[i] = if s[i] == s[i+1] { ls[i+1] } else { s[i] < s[i+1] };
+    }
+    let mut sum_l = vec![0; upper+1];
+    let mut sum_s = vec![0; upper+1];
+    for i in 0..n {
+        if !ls[i] {
+            sum_s[s[i]] += 1;
+        } else {
+            sum_l[s[i]+1] += 1;
+        }
+    }
+    for i in 0..upper+1 {
+        sum_s[i] += sum_l[i];
+        if i < upper {
+            sum_l[i+1] += sum_s[i];
+        }
+    }
+
+    // LMS: 左がL型の S型
+    let mut lms_map = vec![SA_NONE; n+1];
+    let mut lms = vec![];
+    for i in 1..n {
+        if !ls[i-1] && ls[i] {
+            lms_map[i] = lms.len();
+            lms.push(i);
+        }
+    }
+    let m = lms.len();
+    let mut sa = vec![SA_NONE; n];
+    sa_induce(&lms, s, &ls, &sum_s, &sum_l, &mut sa);
+
+    if m > 0 {
+        // LMS部分文字列に番号を振って再帰する
+        let sorted_lms: Vec<usize> = sa.iter().cloned().filter(|&v| lms_map[v] != SA_NONE).collect();
+        let mut rec_s = vec![0; m];
+        let mut rec_upper = 0;
+        for i in 1..m {
+            let mut l = sorted_lms[i-1];
+            let mut r = sorted_lms[i];
+            let end_l = if lms_map[l]+1 < m { lms[lms_map[l]+1] } else { n };
+            let end_r = if lms_map[r]+1 < m { lms[lms_map[r]+1] } else { n };
+            let mut same = true;
+            if end_l - l != end_r - r {
+                same = false;
+            } else {
+                while l < end_l {
+                    if s[l] != s[r] {
+                        break;
+                    }
+                    l += 1;
+                    r += 1;
+                }
+                if l == n || s[l] != s[r] {
+                    same = false;
+                }
+            }
+            if !same {
+                rec_upper += 1;
+            }
+            rec_s[lms_map[sorted_lms[i]]] = rec_upper;
+        }
+        let rec_sa = sa_is(&rec_s, rec_upper);
+        let sorted_lms: Vec<usize> = rec_sa.iter().map(|&i| lms[i]).collect();
+        sa_induce(&sorted_lms, s, &ls, &sum_s, &sum_l, &mut sa);
+    }
+    sa
+}
+
+#[snippet("SuffixArray")]
+pub struct SuffixArray {
     // sのうち前からSA[i]個消したやつが辞書順i番目のsuffixである
-    sa: Vec<usize>,
+    pub sa: Vec<usize>,
+    pub lcp: Vec<usize>,
     s: Vec<u64>
 }
 #[snippet("SuffixArray")]
 impl SuffixArray {
     pub fn new(s: Vec<u64>) -> Self {
-        let mut s = s;
-        s.push('$' as u64);
-        let mut sa = Self::sort_cyclic_shifts(&s);
-        sa.remove(0);
-        s.remove(s.len()-1);
+        let mut vals = s.clone();
+        vals.sort();
+        vals.dedup();
+        let t: Vec<usize> = s.iter().map(|x| vals.binary_search(x).unwrap()).collect();
+        let upper = if vals.is_empty() { 0 } else { vals.len() - 1 };
+        let sa = sa_is(&t, upper);
+        let lcp = Self::kasai(&t, &sa);
         SuffixArray {
             sa: sa,
+            lcp: lcp,
             s: s
         }
     }
-    fn sort_cyclic_shifts(s: &[u64]) -> Vec<usize> {
+    fn kasai<T: PartialEq>(s: &[T], sa: &[usize]) -> Vec<usize> {
         let n = s.len();
-        const alphabet: usize = 256;
-        let mut p = vec![0;n];
-        let mut c = vec![0;n];
-        let mut cnt = vec![0; std::cmp::max(alphabet,n)];
-
-        for i in 0..n {
-            cnt[s[i] as usize] += 1;
-        }
-        for i in 1..alphabet {
-            cnt[i] += cnt[i-1];
+        if n == 0 {
+            return vec![];
         }
+        let mut rank = vec![0; n];
         for i in 0..n {
-            cnt[s[i] as usize] -= 1;
-            p[cnt[s[i] as usize]] = i;
-        }
-        c[p[0]] = 0;
-        let mut classes = 1;
-        for i in 1..n {
-            if s[p[i]] != s[p[i-1]] {
-                classes += 1;
-            }
-            c[p[i]] = classes - 1;
+            rank[sa[i]] = i;
         }
-
-        let mut pn = vec![0;n];
-        let mut cn = vec![0;n];
-        for k in 0.. { // OK in 1.15
-            if (1<<k) >= n { break; }
-
-            for i in 0..n {
-                if p[i] >= (1<<k) {
-                    pn[i] = p[i] - (1<<k);
-                } else {
-                    pn[i] = p[i] + n - (1<<k);
-                }
-            }
-            for i in 0..classes {
-                cnt[i] = 0;
-            }
-            for i in 0..n {
-                cnt[c[pn[i]]] += 1;
+        let mut lcp = vec![0; n-1];
+        let mut h: usize = 0;
+        // 前のsuffixとのLCPはsuffixを一つ進めると高々1しか減らない
+        for i in 0..n {
+            if h > 0 {
+                h -= 1;
             }
-            for i in 1..classes {
-                cnt[i] += cnt[i-1];
+            if rank[i] == 0 {
+                continue;
             }
-            for i in (0..n).rev() {
-                cnt[c[pn[i]]] -= 1;
-                p[cnt[c[pn[i]]]] = pn[i];
+            let j = sa[rank[i]-1];
+            while i+h < n && j+h < n && s[i+h] == s[j+h] {
+                h += 1;
             }
-            cn[p[0]] = 0;
-            classes = 1;
-            for i in 1..n {
-                let cur = (c[p[i]], c[(p[i] + (1<<k)) %n]);
-                let prev = (c[p[i-1]], c[(p[i-1] + (1<<k)) %n]);
-                if cur != prev {
-                    classes += 1;
-                }
-                cn[p[i]] = classes - 1;
+            lcp[rank[i]-1] = h;
+        }
+        lcp
+    }
+    #[doc = "異なる部分文字列の数(空文字列を除く)"]
+    pub fn distinct_substrings(&self) -> usize {
+        let n = self.s.len();
+        n * (n+1) / 2 - self.lcp.iter().sum::<usize>()
+    }
+    #[doc = "2回以上現れる最長の部分文字列を(開始位置, 長さ)で返す。なければ長さ0"]
+    pub fn longest_repeated_substring(&self) -> (usize, usize) {
+        let mut best = (0, 0);
+        for i in 0..self.lcp.len() {
+            if self.lcp[i] > best.1 {
+                best = (self.sa[i], self.lcp[i]);
             }
-            let tmp = c;
-            c = cn;
-            cn = tmp
         }
-        p
+        best
     }
     // 文字列比較をする。バイナリサーチのために必要
     // sの方が辞書順で前ならばtrue
@@ -138,6 +237,35 @@ impl SuffixArray {
     pub fn right_most_index(&self, t: &[u64]) -> usize {
         self.sa[self.lower_bound(t)]
     }
+    // suffixの先頭|t|文字がt以下ならtrue
+    fn le_prefix(s: &[u64], t: &[u64], si: usize) -> bool {
+        let m = std::cmp::min(s.len() - si, t.len());
+        s[si..si+m] <= t[..m]
+    }
+    #[doc = "tで始まらずtより大きい最初のsuffixの順位"]
+    fn upper_bound(&self, t: &[u64]) -> usize {
+        let mut low: i64 = -1;
+        let mut high: i64 = self.sa.len() as i64;
+        while high - low > 1 {
+            let mid = (low+high) / 2;
+            if Self::le_prefix(&self.s, t, self.sa[mid as usize]) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        return high as usize
+    }
+    #[doc = "sにtが現れる回数。O(mlogn)"]
+    pub fn count_occurrences(&self, t: &[u64]) -> usize {
+        self.upper_bound(t) - self.lower_bound(t)
+    }
+    #[doc = "sにtが現れる位置を昇順に返す。O(mlogn + k logk)"]
+    pub fn all_occurrences(&self, t: &[u64]) -> Vec<usize> {
+        let mut v = self.sa[self.lower_bound(t)..self.upper_bound(t)].to_vec();
+        v.sort();
+        v
+    }
 }
 
 fn as_v(s: &str) -> Vec<u64> {
@@ -165,4 +293,78 @@ fn test_suffix_array() {
     assert_eq!(sa.right_most_index(&as_v("bra")), 8);
     assert_eq!(sa.right_most_index(&as_v("abra")), 7);
     assert_eq!(sa.right_most_index(&as_v("abr")), 7);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_suffix_array_lcp() {
+    let s = "abracadabra";
+    let sa = SuffixArray::new(as_v(s));
+    assert_eq!(sa.lcp, [1,4,1,1,0,3,0,0,0,2]);
+    assert_eq!(sa.longest_repeated_substring(), (7, 4));
+    assert_eq!(sa.count_occurrences(&as_v("abra")), 2);
+    assert_eq!(sa.count_occurrences(&as_v("a")), 5);
+    assert_eq!(sa.count_occurrences(&as_v("abrac")), 1);
+    assert_eq!(sa.count_occurrences(&as_v("abrab")), 0);
+    assert_eq!(sa.count_occurrences(&as_v("")), 11);
+    assert_eq!(sa.all_occurrences(&as_v("a")), [0,3,5,7,10]);
+    assert_eq!(sa.all_occurrences(&as_v("bra")), [1,8]);
+    assert_eq!(sa.all_occurrences(&as_v("z")), []);
+
+    let sa = SuffixArray::new(vec![]);
+    assert_eq!(sa.distinct_substrings(), 0);
+    assert_eq!(sa.longest_repeated_substring(), (0, 0));
+}
+
+#[test]
+fn test_suffix_array_random() {
+    use crate::xorshift::Xorshift;
+    use std::collections::HashSet;
+    let mut rand = Xorshift::new();
+    // '$'(36)や256以上の値も含める
+    let alphabets = [vec![36, 37], vec![0, 36, 255, 256, 1 << 40, std::u64::MAX], vec![1, 2, 3, 4]];
+    for alphabet in &alphabets {
+        for _ in 0..100 {
+            let n = rand.rand(40) as usize;
+            let s: Vec<u64> = (0..n).map(|_| alphabet[rand.rand(alphabet.len() as u64) as usize]).collect();
+            let sa = SuffixArray::new(s.clone());
+            let mut naive: Vec<usize> = (0..n).collect();
+            naive.sort_by(|&i, &j| s[i..].cmp(&s[j..]));
+            assert_eq!(sa.sa, naive);
+            for i in 1..n {
+                let (a, b) = (&s[naive[i-1]..], &s[naive[i]..]);
+                let l = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+                assert_eq!(sa.lcp[i-1], l);
+            }
+            let mut set = HashSet::new();
+            for i in 0..n {
+                for j in i+1..n+1 {
+                    set.insert(&s[i..j]);
+                }
+            }
+            assert_eq!(sa.distinct_substrings(), set.len());
+            let (p, l) = sa.longest_repeated_substring();
+            let max_rep = (1..n+1).filter(|&l| (0..n-l+1).any(|i| (i+1..n-l+1).any(|j| s[i..i+l] == s[j..j+l]))).max().unwrap_or(0);
+            assert_eq!(l, max_rep);
+            assert!(sa.count_occurrences(&s[p..p+l]) >= 2 || l == 0);
+            for _ in 0..10 {
+                let m = rand.rand(4) as usize;
+                let t: Vec<u64> = (0..m).map(|_| alphabet[rand.rand(alphabet.len() as u64) as usize]).collect();
+                let occ: Vec<usize> = (0..n).filter(|&i| s[i..].starts_with(&t)).collect();
+                assert_eq!(sa.all_occurrences(&t), occ);
+                assert_eq!(sa.count_occurrences(&t), occ.len());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_suffix_array_long() {
+    let s: Vec<u64> = (0..100000).map(|i| (i % 7 == 0) as u64).collect();
+    let sa = SuffixArray::new(s.clone());
+    let n = s.len();
+    for i in 1..n {
+        let (a, b, l) = (sa.sa[i-1], sa.sa[i], sa.lcp[i-1]);
+        assert_eq!(s[a..a+l], s[b..b+l]);
+        assert!(a+l == n || (b+l < n && s[a+l] < s[b+l]));
+    }
+}
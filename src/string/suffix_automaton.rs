@@ -0,0 +1,267 @@
+use cargo_snippet::snippet;
+use std::collections::BTreeMap;
+
+/// 接尾辞オートマトン
+///
+/// Sの全ての部分文字列を受理する最小のDFA。
+/// 各状態は、出現位置の終端集合(endpos)が等しい部分文字列の集まりで、
+/// その長さは(link先のlen, len]の範囲になる。
+/// 状態数は高々2|S|-1、遷移数は高々3|S|-4。
+///
+/// extendで一文字ずつオンラインに伸ばせる。
+/// 遷移はBTreeMapで持つので、辞書順に辿れる。
+///
+/// 計算量:
+/// extend: O(log σ) amortized
+/// 出現回数、k番目の部分文字列の前計算: O(|S| log |S|)
+
+#[snippet("SuffixAutomaton")]
+pub struct SuffixAutomaton<T> {
+    /// 状態に含まれる最長の文字列の長さ
+    pub len: Vec<usize>,
+    /// suffix link。根は0を指すが使わない
+    pub link: Vec<usize>,
+    pub next: Vec<BTreeMap<T, usize>>,
+    /// 最初の出現の終端位置(0-indexed)
+    pub first_pos: Vec<usize>,
+    /// cloneで作った状態か
+    pub is_clone: Vec<bool>,
+    /// 文字列全体を表す状態
+    pub last: usize,
+}
+#[snippet("SuffixAutomaton")]
+impl<T: Ord + Copy> SuffixAutomaton<T> {
+    pub fn new() -> Self {
+        SuffixAutomaton {
+            len: vec![0],
+            link: vec![0],
+            next: vec![BTreeMap::new()],
+            first_pos: vec![0],
+            is_clone: vec![false],
+            last: 0,
+        }
+    }
+    pub fn from_slice(s: &[T]) -> Self {
+        let mut sam = Self::new();
+        for &c in s {
+            sam.extend(c);
+        }
+        sam
+    }
+    fn new_state(&mut self, len: usize, link: usize, next: BTreeMap<T, usize>, first_pos: usize, is_clone: bool) -> usize {
+        self.len.push(len);
+        self.link.push(link);
+        self.next.push(next);
+        self.first_pos.push(first_pos);
+        self.is_clone.push(is_clone);
+        self.len.len() - 1
+    }
+    pub fn size(&self) -> usize {
+        self.len.len()
+    }
+    pub fn extend(&mut self, c: T) {
+        let len = self.len[self.last] + 1;
+        let cur = self.new_state(len, 0, BTreeMap::new(), len - 1, false);
+        let mut p = Some(self.last);
+        self.last = cur;
+        while let Some(q) = p {
+            if self.next[q].contains_key(&c) {
+                break;
+            }
+            self.next[q].insert(c, cur);
+            p = if q == 0 { None } else { Some(self.link[q]) };
+        }
+        let p = match p {
+            None => return,
+            Some(p) => p,
+        };
+        let q = self.next[p][&c];
+        if self.len[p] + 1 == self.len[q] {
+            self.link[cur] = q;
+            return;
+        }
+        // qを分割する
+        let next = self.next[q].clone();
+        let (link, first_pos) = (self.link[q], self.first_pos[q]);
+        let clone = self.new_state(self.len[p] + 1, link, next, first_pos, true);
+        let mut p = Some(p);
+        while let Some(r) = p {
+            if self.next[r].get(&c) != Some(&q) {
+                break;
+            }
+            self.next[r].insert(c, clone);
+            p = if r == 0 { None } else { Some(self.link[r]) };
+        }
+        self.link[q] = clone;
+        self.link[cur] = clone;
+    }
+    /// 状態をlenの降順に並べたもの。linkを辿る順の逆になる
+    fn order_by_len_desc(&self) -> Vec<usize> {
+        let mut ord: Vec<usize> = (0..self.size()).collect();
+        ord.sort_by(|&a, &b| self.len[b].cmp(&self.len[a]));
+        ord
+    }
+    /// 各状態の文字列の出現回数(= endposの大きさ)
+    pub fn occurrence_counts(&self) -> Vec<usize> {
+        let mut cnt = vec![0; self.size()];
+        for v in 1..self.size() {
+            if !self.is_clone[v] {
+                cnt[v] = 1;
+            }
+        }
+        for v in self.order_by_len_desc() {
+            if v != 0 {
+                let l = self.link[v];
+                cnt[l] += cnt[v];
+            }
+        }
+        cnt
+    }
+    fn walk(&self, t: &[T]) -> Option<usize> {
+        let mut v = 0;
+        for c in t {
+            v = *self.next[v].get(c)?;
+        }
+        Some(v)
+    }
+    pub fn contains(&self, t: &[T]) -> bool {
+        self.walk(t).is_some()
+    }
+    /// tの出現回数。複数回呼ぶならoccurrence_countsを使い回すこと
+    pub fn count_occurrences(&self, t: &[T]) -> usize {
+        match self.walk(t) {
+            None => 0,
+            Some(v) => if t.is_empty() { self.len[self.last] + 1 } else { self.occurrence_counts()[v] },
+        }
+    }
+    /// tが最初に現れる開始位置
+    pub fn first_occurrence(&self, t: &[T]) -> Option<usize> {
+        let v = self.walk(t)?;
+        if t.is_empty() {
+            return Some(0);
+        }
+        Some(self.first_pos[v] + 1 - t.len())
+    }
+    /// 異なる部分文字列の数(空文字列を除く)
+    pub fn distinct_substrings(&self) -> u64 {
+        (1..self.size()).map(|v| (self.len[v] - self.len[self.link[v]]) as u64).sum()
+    }
+    /// paths[v] = vから辿れる文字列の数(空文字列を含む)
+    fn paths(&self) -> Vec<u64> {
+        let mut paths = vec![1; self.size()];
+        for v in self.order_by_len_desc() {
+            for &u in self.next[v].values() {
+                paths[v] += paths[u];
+            }
+        }
+        paths
+    }
+    /// 異なる部分文字列を辞書順に並べたときのk番目(0-indexed)
+    pub fn kth_substring(&self, k: u64) -> Option<Vec<T>> {
+        let paths = self.paths();
+        // 根の空文字列を除く
+        if k + 1 >= paths[0] {
+            return None;
+        }
+        let mut k = k;
+        let mut v = 0;
+        let mut res = vec![];
+        loop {
+            for (&c, &u) in &self.next[v] {
+                if k < paths[u] {
+                    res.push(c);
+                    v = u;
+                    break;
+                }
+                k -= paths[u];
+            }
+            if k == 0 {
+                return Some(res);
+            }
+            // uで終わる文字列自体を飛ばす
+            k -= 1;
+        }
+    }
+    /// Sとtの最長共通部分文字列を(tでの開始位置, 長さ)で返す
+    pub fn longest_common_substring(&self, t: &[T]) -> (usize, usize) {
+        let mut v = 0;
+        let mut l = 0;
+        let mut best = (0, 0);
+        for (i, c) in t.iter().enumerate() {
+            while v != 0 && !self.next[v].contains_key(c) {
+                v = self.link[v];
+                l = self.len[v];
+            }
+            if let Some(&u) = self.next[v].get(c) {
+                v = u;
+                l += 1;
+            }
+            if l > best.1 {
+                best = (i + 1 - l, l);
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+fn all_substrings(s: &[u64]) -> Vec<Vec<u64>> {
+    let mut v = vec![];
+    for i in 0..s.len() {
+        for j in i+1..s.len()+1 {
+            v.push(s[i..j].to_vec());
+        }
+    }
+    v.sort();
+    v.dedup();
+    v
+}
+
+#[test]
+fn test_suffix_automaton() {
+    let s: Vec<u64> = "abcbc".chars().map(|c| c as u64).collect();
+    let sam = SuffixAutomaton::from_slice(&s);
+    assert_eq!(sam.distinct_substrings(), 12);
+    let t = |x: &str| x.chars().map(|c| c as u64).collect::<Vec<_>>();
+    assert_eq!(sam.count_occurrences(&t("bc")), 2);
+    assert_eq!(sam.count_occurrences(&t("cb")), 1);
+    assert_eq!(sam.count_occurrences(&t("ca")), 0);
+    assert_eq!(sam.first_occurrence(&t("c")), Some(2));
+    assert_eq!(sam.first_occurrence(&t("bcbc")), Some(1));
+    assert_eq!(sam.first_occurrence(&t("abd")), None);
+    assert_eq!(sam.kth_substring(0), Some(t("a")));
+    assert_eq!(sam.kth_substring(5), Some(t("b")));
+    assert_eq!(sam.kth_substring(11), Some(t("cbc")));
+    assert_eq!(sam.kth_substring(12), None);
+    assert_eq!(sam.longest_common_substring(&t("xxcbcby")), (2, 3));
+}
+
+#[test]
+fn test_suffix_automaton_random() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    for _ in 0..100 {
+        let n = rand.rand(30) as usize;
+        let sigma = 1 + rand.rand(4);
+        let s: Vec<u64> = (0..n).map(|_| rand.rand(sigma)).collect();
+        let sam = SuffixAutomaton::from_slice(&s);
+        assert!(sam.size() <= std::cmp::max(2 * n, 2));
+        let subs = all_substrings(&s);
+        assert_eq!(sam.distinct_substrings(), subs.len() as u64);
+        for (k, sub) in subs.iter().enumerate() {
+            assert_eq!(sam.kth_substring(k as u64).as_ref(), Some(sub));
+            let m = sub.len();
+            let occ: Vec<usize> = (0..n+1-m).filter(|&i| s[i..i+m] == sub[..]).collect();
+            assert_eq!(sam.count_occurrences(sub), occ.len());
+            assert_eq!(sam.first_occurrence(sub), Some(occ[0]));
+        }
+        assert_eq!(sam.kth_substring(subs.len() as u64), None);
+
+        let m = rand.rand(30) as usize;
+        let t: Vec<u64> = (0..m).map(|_| rand.rand(sigma)).collect();
+        let (p, l) = sam.longest_common_substring(&t);
+        assert!(sam.contains(&t[p..p+l]));
+        let best = all_substrings(&t).into_iter().filter(|x| sam.contains(x)).map(|x| x.len()).max().unwrap_or(0);
+        assert_eq!(l, best);
+    }
+}
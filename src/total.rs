@@ -4,7 +4,7 @@ use cargo_snippet::snippet;
 use std::cmp::Ordering;
 
 #[snippet("Total")]
-#[derive(PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 /// Implement Eq and Ord for a type which has only PartialEq and PartialOrd.
 /// It is useful when sorting a Vec of f64
 pub struct Total<T>(pub T);
@@ -151,4 +151,237 @@ fn test_weighted_union_find() {
     assert!(wuf.root(3) != wuf.root(1));
     wuf.merge(1,4,8);
     assert_eq!(wuf.weight(4) - wuf.weight(0), 10);
-}
\ No newline at end of file
+}
+/// 巻き戻し可能なUnionFind
+///
+/// 経路圧縮をせず、サイズによるマージだけを行う。
+/// mergeで書き換えた箇所を履歴に積み、rollbackで戻す。
+///
+/// 計算量:
+/// root, merge: O(logN)
+/// rollback: 戻したmerge1回につきO(1)
+
+#[snippet("RollbackUnionFind")]
+#[snippet("OfflineDynamicConnectivity")]
+pub struct RollbackUnionFind {
+    par: Vec<usize>,
+    size: Vec<usize>,
+    /// (親になった根, 子になった根)
+    history: Vec<(usize, usize)>,
+}
+
+#[snippet("RollbackUnionFind")]
+#[snippet("OfflineDynamicConnectivity")]
+impl RollbackUnionFind {
+    pub fn new(n: usize) -> RollbackUnionFind {
+        RollbackUnionFind {
+            par: (0..n).collect::<Vec<usize>>(),
+            size: vec![1; n],
+            history: vec![],
+        }
+    }
+
+    pub fn root(&self, x: usize) -> usize {
+        let mut x = x;
+        while self.par[x] != x {
+            x = self.par[x];
+        }
+        x
+    }
+
+    pub fn size(&self, x: usize) -> usize {
+        self.size[self.root(x)]
+    }
+
+    pub fn same(&self, x: usize, y: usize) -> bool {
+        self.root(x) == self.root(y)
+    }
+
+    pub fn merge(&mut self, x: usize, y: usize) -> bool {
+        let mut a = self.root(x);
+        let mut b = self.root(y);
+        if a == b { return false; }
+
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.size[a] += self.size[b];
+        self.par[b] = a;
+        self.history.push((a, b));
+        true
+    }
+
+    /// 今の状態。rollbackに渡す
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// 直前に成功したmergeを一つ取り消す
+    pub fn undo(&mut self) {
+        let (a, b) = self.history.pop().unwrap();
+        self.par[b] = b;
+        self.size[a] -= self.size[b];
+    }
+
+    /// snapshotの時点まで戻す
+    pub fn rollback(&mut self, snapshot: usize) {
+        while self.history.len() > snapshot {
+            self.undo();
+        }
+    }
+}
+
+#[test]
+fn test_rollback_union_find() {
+    let mut uf = RollbackUnionFind::new(5);
+    uf.merge(1, 4);
+    let s = uf.snapshot();
+    assert!(uf.merge(2, 3));
+    assert!(uf.merge(1, 3));
+    assert!(!uf.merge(4, 2));
+    assert!(uf.same(2, 4));
+    assert_eq!(uf.size(1), 4);
+    uf.undo();
+    assert!(!uf.same(2, 4));
+    assert!(uf.same(2, 3));
+    uf.rollback(s);
+    assert!(!uf.same(2, 3));
+    assert!(uf.same(1, 4));
+    assert_eq!(uf.size(4), 2);
+    uf.rollback(0);
+    assert_eq!(uf.size(4), 1);
+}
+
+/// オフライン動的連結性
+///
+/// 辺の追加、削除、連結判定の列を先に全部受け取り、まとめて答える。
+/// 各辺が存在する時間区間を時間軸のセグ木のO(logQ)個のノードに載せ、
+/// セグ木をDFSしながらRollbackUnionFindでmergeとrollbackをする。
+/// 多重辺も扱える。
+///
+/// 計算量: O(Q logQ logN)
+
+#[snippet("OfflineDynamicConnectivity")]
+pub struct OfflineDynamicConnectivity {
+    n: usize,
+    /// 時刻ごとの連結判定。判定でない時刻はNone
+    queries: Vec<Option<(usize, usize)>>,
+    /// 辺 -> まだ削除されていない追加の時刻
+    open: std::collections::HashMap<(usize, usize), Vec<usize>>,
+    /// (開始, 終了, u, v)。[開始,終了)の間存在する
+    intervals: Vec<(usize, usize, usize, usize)>,
+}
+
+#[snippet("OfflineDynamicConnectivity")]
+impl OfflineDynamicConnectivity {
+    pub fn new(n: usize) -> OfflineDynamicConnectivity {
+        OfflineDynamicConnectivity {
+            n: n,
+            queries: vec![],
+            open: std::collections::HashMap::new(),
+            intervals: vec![],
+        }
+    }
+    fn key(u: usize, v: usize) -> (usize, usize) {
+        if u < v { (u, v) } else { (v, u) }
+    }
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        let t = self.queries.len();
+        self.open.entry(Self::key(u, v)).or_insert(vec![]).push(t);
+        self.queries.push(None);
+    }
+    /// 存在する辺であること
+    pub fn remove_edge(&mut self, u: usize, v: usize) {
+        let t = self.queries.len();
+        let s = self.open.get_mut(&Self::key(u, v)).unwrap().pop().unwrap();
+        self.intervals.push((s, t, u, v));
+        self.queries.push(None);
+    }
+    /// この時点でuとvが連結か
+    pub fn query(&mut self, u: usize, v: usize) {
+        self.queries.push(Some((u, v)));
+    }
+    /// queryの答えを呼んだ順に返す
+    pub fn solve(mut self) -> Vec<bool> {
+        let q = self.queries.len();
+        for (&(u, v), ts) in &self.open {
+            for &s in ts {
+                self.intervals.push((s, q, u, v));
+            }
+        }
+        let mut size = 1;
+        while size < q {
+            size *= 2;
+        }
+        let mut edges = vec![vec![]; 2 * size];
+        for &(s, t, u, v) in &self.intervals {
+            let mut l = s + size;
+            let mut r = t + size;
+            while l < r {
+                if l & 1 == 1 {
+                    edges[l].push((u, v));
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    edges[r].push((u, v));
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        let mut uf = RollbackUnionFind::new(self.n);
+        let mut ans = vec![];
+        Self::dfs(1, size, &edges, &self.queries, &mut uf, &mut ans);
+        ans
+    }
+    fn dfs(k: usize, size: usize, edges: &[Vec<(usize, usize)>], queries: &[Option<(usize, usize)>], uf: &mut RollbackUnionFind, ans: &mut Vec<bool>) {
+        let s = uf.snapshot();
+        for &(u, v) in &edges[k] {
+            uf.merge(u, v);
+        }
+        if k >= size {
+            if let Some(&Some((u, v))) = queries.get(k - size) {
+                ans.push(uf.same(u, v));
+            }
+        } else {
+            Self::dfs(2 * k, size, edges, queries, uf, ans);
+            Self::dfs(2 * k + 1, size, edges, queries, uf, ans);
+        }
+        uf.rollback(s);
+    }
+}
+
+#[test]
+fn test_offline_dynamic_connectivity() {
+    use crate::xorshift::Xorshift;
+    let mut rand = Xorshift::new();
+    let n = 8;
+    let mut dc = OfflineDynamicConnectivity::new(n);
+    let mut edges: Vec<(usize, usize)> = vec![];
+    let mut expected = vec![];
+    for _ in 0..2000 {
+        let u = rand.rand(n as u64) as usize;
+        let v = rand.rand(n as u64) as usize;
+        match rand.rand(3) {
+            0 => {
+                dc.add_edge(u, v);
+                edges.push((u, v));
+            },
+            1 if !edges.is_empty() => {
+                let i = rand.rand(edges.len() as u64) as usize;
+                let (u, v) = edges.swap_remove(i);
+                dc.remove_edge(u, v);
+            },
+            _ => {
+                dc.query(u, v);
+                let mut uf = UnionFind::new(n);
+                for &(a, b) in &edges {
+                    uf.merge(a, b);
+                }
+                expected.push(uf.same(u, v));
+            },
+        }
+    }
+    assert_eq!(dc.solve(), expected);
+}
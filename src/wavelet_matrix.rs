@@ -292,7 +292,7 @@ fn test_fid_select_many_blocks() {
 }
 
 #[snippet("WaveletMatrix")]
-struct WM {
+pub struct WM {
     mat: Vec<FID>,
     nzeros: Vec<usize>,
 }